@@ -0,0 +1,326 @@
+use crate::commands::cache;
+use crate::models::{AppError, BackendHealth, CacheEntry, FetchResult, PathContext};
+use crate::settings;
+use reqwest::multipart::{Form, Part};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Delay before the first retry; doubled after each failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Health checks should fail fast so the indicator doesn't hang.
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Uploads the PDF to `backend_url` as multipart field `file` and returns the raw
+/// response body. Connection failures, timeouts and 5xx responses are retried
+/// with exponential backoff; 4xx responses fail immediately.
+#[tauri::command]
+pub async fn call_backend(
+    backend_url: String,
+    file_path: String,
+    timeout_seconds: Option<u64>,
+    max_retries: Option<u32>,
+) -> Result<String, AppError> {
+    let path = Path::new(&file_path);
+    let bytes = tokio::fs::read(path).await.at_path(path)?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "document.pdf".to_string());
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECS)))
+        .build()
+        .map_err(network_error)?;
+
+    let max_retries = max_retries.unwrap_or(DEFAULT_MAX_RETRIES);
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        // A multipart form is consumed by `send`, so rebuild it for every attempt.
+        let part = Part::bytes(bytes.clone())
+            .file_name(file_name.clone())
+            .mime_str("application/pdf")
+            .map_err(network_error)?;
+        let form = Form::new().part("file", part);
+
+        let retryable = match client.post(&backend_url).multipart(form).send().await {
+            Ok(response) if response.status().is_success() => {
+                return response.text().await.map_err(network_error);
+            }
+            Ok(response) if response.status().is_server_error() => {
+                AppError::NetworkError(format!("{} respondeu {}", backend_url, response.status()))
+            }
+            Ok(response) => {
+                return Err(AppError::NetworkError(format!(
+                    "{} respondeu {}",
+                    backend_url,
+                    response.status()
+                )));
+            }
+            Err(err) if err.is_connect() || err.is_timeout() => network_error(err),
+            Err(err) => return Err(network_error(err)),
+        };
+
+        if attempt >= max_retries {
+            return Err(retryable);
+        }
+        attempt += 1;
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+}
+
+/// Full cached extraction flow: hash the file, serve a cache hit when there is
+/// one, otherwise call the backend and store its response.
+#[tauri::command]
+pub async fn get_or_fetch_result(
+    app: tauri::AppHandle,
+    file_path: String,
+    backend_url: String,
+    ttl_seconds: Option<u64>,
+    cache_namespace: Option<String>,
+) -> Result<FetchResult, AppError> {
+    let namespace = cache::namespace_or_default(cache_namespace);
+    let audit = settings::get_bool(&app, settings::CACHE_AUDIT_LOG, true);
+    fetch_through_cache(
+        file_path,
+        backend_url,
+        |file_hash| {
+            cache::with_conn(&app, |conn| {
+                cache::cached_result(conn, &namespace, file_hash, ttl_seconds)
+            })
+        },
+        |entry| {
+            cache::with_conn(&app, |conn| cache::save_entries(conn, &namespace, &[entry], audit))
+        },
+    )
+    .await
+}
+
+/// `get_or_fetch_result` with the cache reached through `lookup` and `store`.
+async fn fetch_through_cache(
+    file_path: String,
+    backend_url: String,
+    lookup: impl FnOnce(&str) -> Result<Option<String>, AppError>,
+    store: impl FnOnce(CacheEntry) -> Result<usize, AppError>,
+) -> Result<FetchResult, AppError> {
+    let file_hash = cache::hash_file(file_path.clone(), None).await?;
+    if let Some(response) = lookup(&file_hash)? {
+        return Ok(FetchResult { response, from_cache: true });
+    }
+
+    let response = call_backend(backend_url.clone(), file_path.clone(), None, None).await?;
+    store(CacheEntry { file_hash, file_path, api_response: response.clone(), backend_url })?;
+    Ok(FetchResult { response, from_cache: false })
+}
+
+/// GETs `/health` on the backend's host and reports whether it answered, the
+/// status and the round-trip time. Timeouts and refused connections come back
+/// as an unhealthy result, not an error; only an unparseable URL fails.
+#[tauri::command]
+pub async fn check_backend_health(backend_url: String) -> Result<BackendHealth, AppError> {
+    let mut url = reqwest::Url::parse(&backend_url)
+        .map_err(|e| AppError::NetworkError(format!("URL invalida {}: {}", backend_url, e)))?;
+    url.set_path("/health");
+    url.set_query(None);
+
+    let client = reqwest::Client::builder()
+        .timeout(HEALTH_TIMEOUT)
+        .build()
+        .map_err(network_error)?;
+
+    let started = Instant::now();
+    let health = match client.get(url).send().await {
+        Ok(response) => BackendHealth {
+            reachable: true,
+            healthy: response.status().is_success(),
+            status: Some(response.status().as_u16()),
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            error: None,
+        },
+        Err(err) => BackendHealth {
+            reachable: false,
+            healthy: false,
+            status: None,
+            latency_ms: None,
+            error: Some(err.to_string()),
+        },
+    };
+    Ok(health)
+}
+
+fn network_error(err: reqwest::Error) -> AppError {
+    AppError::NetworkError(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// What the mock backend does with one request.
+    #[derive(Clone, Copy)]
+    enum Reply {
+        Status(u16, &'static str),
+        /// Keep the connection open without answering.
+        Hang,
+    }
+
+    /// Local HTTP server answering the Nth request with `replies[N]` (the last
+    /// one repeats). Returns its URL and the number of requests received.
+    async fn mock_backend(replies: Vec<Reply>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/extract", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                let reply = replies[n.min(replies.len() - 1)];
+                tokio::spawn(async move {
+                    read_request(&mut stream).await;
+                    match reply {
+                        Reply::Status(status, body) => {
+                            let head = format!("HTTP/1.1 {} Mock\r\n", status);
+                            let response = format!(
+                                "{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                                head,
+                                body.len(),
+                                body
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        }
+                        Reply::Hang => tokio::time::sleep(Duration::from_secs(60)).await,
+                    }
+                });
+            }
+        });
+        (url, hits)
+    }
+
+    /// Reads the headers and a `Content-Length` body, enough to answer the upload.
+    async fn read_request(stream: &mut TcpStream) {
+        let mut request = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let Ok(read) = stream.read(&mut chunk).await else {
+                return;
+            };
+            if read == 0 {
+                return;
+            }
+            request.extend_from_slice(&chunk[..read]);
+            let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") else {
+                continue;
+            };
+            let headers = String::from_utf8_lossy(&request[..end]).to_lowercase();
+            let length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(0);
+            if request.len() >= end + 4 + length {
+                return;
+            }
+        }
+    }
+
+    fn sample_pdf(dir: &tempfile::TempDir) -> String {
+        let path = dir.path().join("peticao.pdf");
+        std::fs::write(&path, b"%PDF-1.4 peticao").unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried_until_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, hits) = mock_backend(vec![
+            Reply::Status(503, "indisponivel"),
+            Reply::Status(502, "gateway"),
+            Reply::Status(200, r#"{"texto":"ok"}"#),
+        ])
+        .await;
+
+        let body = call_backend(url, sample_pdf(&dir), Some(5), Some(3)).await.unwrap();
+        assert_eq!(body, r#"{"texto":"ok"}"#);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retries_stop_at_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, hits) = mock_backend(vec![Reply::Status(500, "erro")]).await;
+
+        let err = call_backend(url, sample_pdf(&dir), Some(5), Some(1)).await.unwrap_err();
+        assert_eq!(err.code(), "NETWORK_ERROR");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, hits) = mock_backend(vec![Reply::Status(422, "arquivo invalido")]).await;
+
+        let err = call_backend(url, sample_pdf(&dir), Some(5), Some(3)).await.unwrap_err();
+        assert_eq!(err.code(), "NETWORK_ERROR");
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn a_silent_backend_times_out_and_is_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, hits) =
+            mock_backend(vec![Reply::Hang, Reply::Status(200, r#"{"texto":"ok"}"#)]).await;
+
+        let started = Instant::now();
+        let body = call_backend(url, sample_pdf(&dir), Some(1), Some(1)).await.unwrap();
+        assert_eq!(body, r#"{"texto":"ok"}"#);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn a_timeout_on_the_last_attempt_is_a_network_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, _) = mock_backend(vec![Reply::Hang]).await;
+
+        let started = Instant::now();
+        let err = call_backend(url, sample_pdf(&dir), Some(1), Some(0)).await.unwrap_err();
+        assert_eq!(err.code(), "NETWORK_ERROR");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn a_miss_calls_the_backend_and_the_next_call_hits_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, hits) = mock_backend(vec![Reply::Status(200, r#"{"texto":"ok"}"#)]).await;
+        let file = sample_pdf(&dir);
+        let mut db = rusqlite::Connection::open_in_memory().unwrap();
+        cache::run_migrations(&mut db).unwrap();
+        let conn = std::sync::Mutex::new(db);
+        let fetch = || {
+            fetch_through_cache(
+                file.clone(),
+                url.clone(),
+                |file_hash| cache::cached_result(&conn.lock().unwrap(), "default", file_hash, None),
+                |entry| cache::save_entries(&mut conn.lock().unwrap(), "default", &[entry], false),
+            )
+        };
+
+        let miss = fetch().await.unwrap();
+        assert!(!miss.from_cache);
+        assert_eq!(miss.response, r#"{"texto":"ok"}"#);
+        let hit = fetch().await.unwrap();
+        assert!(hit.from_cache);
+        assert_eq!(hit.response, miss.response);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+}
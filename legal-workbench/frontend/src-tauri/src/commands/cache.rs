@@ -1,50 +1,379 @@
-use crate::models::AppError;
-use rusqlite::{Connection, params};
+use crate::models::{
+    AppError, BackendCacheEntry, CacheAuditEntry, CacheEntry, CacheExportRow, CacheIntegrity,
+    CacheStats, DiskSpace, HashAlgorithm, PathContext, RecentFile,
+};
+use crate::commands::filesystem;
+use crate::settings;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rayon::prelude::*;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, params};
 use sha2::{Sha256, Digest};
-use std::io::Read;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 use tauri::Manager;
 
+/// Single cache connection shared by every command. Managed as Tauri state.
+#[derive(Default)]
+pub struct CacheDb {
+    conn: Mutex<Option<Connection>>,
+}
+
+impl CacheDb {
+    /// Drops the open connection so the next command reopens at the configured path.
+    fn reset(&self) {
+        *self.conn.lock().unwrap() = None;
+    }
+
+    /// Runs `f` against the connection, opening the db at `path()` and
+    /// migrating it to the current schema on first use, so commands work
+    /// before `init_cache` has run.
+    fn with<T>(
+        &self,
+        path: impl FnOnce() -> PathBuf,
+        f: impl FnOnce(&mut Connection) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let mut conn = self.conn.lock().unwrap();
+        if conn.is_none() {
+            let mut opened = open_connection(&path())?;
+            run_migrations(&mut opened)?;
+            *conn = Some(opened);
+        }
+        f(conn.as_mut().unwrap())
+    }
+
+    /// Called on exit. Waits for any in-flight write (it holds the lock),
+    /// checkpoints the WAL into the main file and closes the connection, so
+    /// the db is complete on its own when the app stops.
+    pub fn close(&self) {
+        let Some(conn) = self.conn.lock().unwrap().take() else {
+            return;
+        };
+        // Nothing to report to on exit; a failed checkpoint is replayed from the
+        // WAL on the next open anyway.
+        let _ = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()));
+        let _ = conn.close();
+    }
+}
+
+/// The `cache_db_path` setting when present, else `<app_data>/cache.db`.
+/// Pointing the setting somewhere else starts a fresh cache; the old file is left as is.
 fn get_db_path(app: &tauri::AppHandle) -> PathBuf {
-    app.path().app_data_dir().unwrap().join("cache.db")
+    settings::get_string(app, settings::CACHE_DB_PATH)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| app.path().app_data_dir().unwrap().join("cache.db"))
+}
+
+/// Fails early with a clear error when the cache directory can't be written.
+fn ensure_writable_dir(dir: &Path) -> Result<(), AppError> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(".cache-write-test");
+    std::fs::File::create(&probe)
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| {
+            AppError::PermissionDenied(format!(
+                "diretorio do cache sem permissao de escrita: {} ({})",
+                dir.display(),
+                e
+            ))
+        })
+}
+
+/// Runs `f` against the shared connection, opening it on first use.
+pub(crate) fn with_conn<T>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&mut Connection) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    app.state::<CacheDb>().with(|| get_db_path(app), f)
+}
+
+/// Opens the cache db in WAL mode so readers don't block during a write, and
+/// lets writers wait on contention instead of failing with `database is locked`.
+fn open_connection(path: &Path) -> Result<Connection, AppError> {
+    let conn = Connection::open(path)?;
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+    conn.busy_timeout(Duration::from_millis(5000))?;
+    Ok(conn)
+}
+
+/// Ordered schema migrations; entry `i` upgrades the db to version `i + 1`.
+/// Append new steps, never edit shipped ones.
+const MIGRATIONS: &[&str] = &[
+    // v1: initial schema. IF NOT EXISTS adopts databases created before versioning.
+    "CREATE TABLE IF NOT EXISTS api_cache (
+        file_hash TEXT PRIMARY KEY,
+        file_path TEXT NOT NULL,
+        api_response TEXT NOT NULL,
+        backend_url TEXT NOT NULL,
+        cached_at INTEGER NOT NULL
+    );",
+    // v2: gzip flag for large responses; existing rows stay uncompressed.
+    "ALTER TABLE api_cache ADD COLUMN compressed INTEGER NOT NULL DEFAULT 0;",
+    // v3: LRU bookkeeping, seeded from the write time.
+    "ALTER TABLE api_cache ADD COLUMN last_accessed INTEGER NOT NULL DEFAULT 0;
+     UPDATE api_cache SET last_accessed = cached_at;",
+    // v4: rendered first-page thumbnails, one per size.
+    "CREATE TABLE IF NOT EXISTS thumbnails (
+        file_hash TEXT NOT NULL,
+        max_dimension INTEGER NOT NULL,
+        png BLOB NOT NULL,
+        created_at INTEGER NOT NULL,
+        PRIMARY KEY (file_hash, max_dimension)
+    );",
+    // v5: text pulled out of PDFs, so extraction survives restarts.
+    "CREATE TABLE IF NOT EXISTS extracted_text (
+        file_hash TEXT PRIMARY KEY,
+        text BLOB NOT NULL,
+        compressed INTEGER NOT NULL DEFAULT 0,
+        extracted_at INTEGER NOT NULL
+    );",
+    // v6: `invalidate_cache_for_backend` filters on backend_url and the TTL
+    // paths (`get_cached_result`, `prune_expired_cache`) on cached_at. Without
+    // these, each is a full scan of api_cache; with them, EXPLAIN QUERY PLAN
+    // reports `SEARCH api_cache USING INDEX` and cost tracks matching rows only.
+    // On 50,000 rows with 1 KB responses, 2,500 matching (`cache_index_timings`,
+    // best of 20, release): backend lookup 6.2 -> 1.1 ms, expiry 5.2 -> 1.4 ms.
+    "CREATE INDEX IF NOT EXISTS idx_api_cache_backend_url ON api_cache (backend_url);
+     CREATE INDEX IF NOT EXISTS idx_api_cache_cached_at ON api_cache (cached_at);",
+    // v7: named caches. The namespace joins the primary key, which SQLite can
+    // only change by rebuilding the table; existing rows land in 'default'.
+    "CREATE TABLE api_cache_v7 (
+        namespace TEXT NOT NULL DEFAULT 'default',
+        file_hash TEXT NOT NULL,
+        file_path TEXT NOT NULL,
+        api_response TEXT NOT NULL,
+        backend_url TEXT NOT NULL,
+        cached_at INTEGER NOT NULL,
+        compressed INTEGER NOT NULL DEFAULT 0,
+        last_accessed INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (namespace, file_hash)
+    );
+     INSERT INTO api_cache_v7
+        (namespace, file_hash, file_path, api_response, backend_url, cached_at, compressed, last_accessed)
+        SELECT 'default', file_hash, file_path, api_response, backend_url, cached_at, compressed, last_accessed
+        FROM api_cache;
+     DROP TABLE api_cache;
+     ALTER TABLE api_cache_v7 RENAME TO api_cache;
+     CREATE INDEX IF NOT EXISTS idx_api_cache_backend_url ON api_cache (backend_url);
+     CREATE INDEX IF NOT EXISTS idx_api_cache_cached_at ON api_cache (cached_at);",
+    // v8: append-only record of cache writes, filled when the audit setting is on.
+    "CREATE TABLE IF NOT EXISTS cache_audit_log (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp INTEGER NOT NULL,
+        namespace TEXT NOT NULL,
+        file_hash TEXT NOT NULL,
+        backend_url TEXT NOT NULL,
+        action TEXT NOT NULL
+    );",
+    // v9: aggregate results for whole folders, keyed by `hash_folder`.
+    "CREATE TABLE IF NOT EXISTS folder_cache (
+        folder_hash TEXT PRIMARY KEY,
+        folder_path TEXT NOT NULL,
+        result BLOB NOT NULL,
+        compressed INTEGER NOT NULL DEFAULT 0,
+        cached_at INTEGER NOT NULL
+    );
+     CREATE INDEX IF NOT EXISTS idx_folder_cache_folder_path ON folder_cache (folder_path);",
+    // v10: single-row rolling extraction throughput, for time estimates.
+    "CREATE TABLE IF NOT EXISTS extraction_stats (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        pages_per_second REAL NOT NULL,
+        samples INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );",
+    // v11: recently opened documents, capped by `record_file_access`.
+    "CREATE TABLE IF NOT EXISTS recent_files (
+        path TEXT PRIMARY KEY,
+        last_opened INTEGER NOT NULL
+    );",
+];
+
+/// Namespace used when a command isn't given one; holds every pre-namespace entry.
+const DEFAULT_NAMESPACE: &str = "default";
+
+pub(crate) fn namespace_or_default(cache_namespace: Option<String>) -> String {
+    cache_namespace.unwrap_or_else(|| DEFAULT_NAMESPACE.to_string())
+}
+
+/// Responses larger than this many bytes are gzipped before storage.
+const COMPRESSION_THRESHOLD: usize = 4 * 1024;
+
+/// Applies every migration newer than the recorded `schema_version`, each in its own transaction.
+pub(crate) fn run_migrations(conn: &mut Connection) -> Result<(), AppError> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)",
+        [],
+    )?;
+    let current: i64 = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )?;
+
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.execute(
+            "INSERT INTO schema_version (version) VALUES (?)",
+            params![index as i64 + 1],
+        )?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Gzips responses above `COMPRESSION_THRESHOLD`; returns the stored value and its flag.
+fn encode_response(response: &str) -> Result<(Value, bool), AppError> {
+    if response.len() <= COMPRESSION_THRESHOLD {
+        return Ok((Value::Text(response.to_string()), false));
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(response.as_bytes())?;
+    Ok((Value::Blob(encoder.finish()?), true))
+}
+
+fn decode_response(value: Value, compressed: bool) -> Result<String, AppError> {
+    match (value, compressed) {
+        (Value::Blob(bytes), true) => {
+            let mut response = String::new();
+            GzDecoder::new(bytes.as_slice()).read_to_string(&mut response)?;
+            Ok(response)
+        }
+        (Value::Text(text), _) => Ok(text),
+        (Value::Blob(bytes), false) => {
+            String::from_utf8(bytes).map_err(|e| AppError::DatabaseError(e.to_string()))
+        }
+        _ => Err(AppError::DatabaseError("api_response com tipo inesperado".to_string())),
+    }
+}
+
+fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Entries with `cached_at` below this are older than `ttl_seconds`.
+fn expiry_cutoff(ttl_seconds: u64) -> i64 {
+    now_secs().saturating_sub(ttl_seconds as i64)
 }
 
 #[tauri::command]
 pub async fn init_cache(app: tauri::AppHandle) -> Result<(), AppError> {
     let db_path = get_db_path(&app);
     if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent)?;
+        ensure_writable_dir(parent)?;
     }
 
-    let conn = Connection::open(&db_path)?;
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS api_cache (
-            file_hash TEXT PRIMARY KEY,
-            file_path TEXT NOT NULL,
-            api_response TEXT NOT NULL,
-            backend_url TEXT NOT NULL,
-            cached_at INTEGER NOT NULL
-        )",
-        [],
-    )?;
-    Ok(())
+    // Reopen in case the configured path changed since the last init;
+    // opening runs the migrations.
+    app.state::<CacheDb>().reset();
+    with_conn(&app, |_| Ok(()))
 }
 
+/// Returns the cached response for `file_hash`. When `ttl_seconds` is given,
+/// entries older than that are deleted and reported as a miss. Every cache
+/// command takes an optional `cache_namespace`; omitted means `"default"`.
 #[tauri::command]
 pub async fn get_cached_result(
     app: tauri::AppHandle,
     file_hash: String,
+    ttl_seconds: Option<u64>,
+    cache_namespace: Option<String>,
 ) -> Result<Option<String>, AppError> {
-    let db_path = get_db_path(&app);
-    let conn = Connection::open(&db_path)?;
+    let namespace = namespace_or_default(cache_namespace);
+    with_conn(&app, |conn| cached_result(conn, &namespace, &file_hash, ttl_seconds))
+}
 
-    let result: Result<String, _> = conn.query_row(
-        "SELECT api_response FROM api_cache WHERE file_hash = ?",
-        params![file_hash],
-        |row| row.get(0),
+pub(crate) fn cached_result(
+    conn: &Connection,
+    namespace: &str,
+    file_hash: &str,
+    ttl_seconds: Option<u64>,
+) -> Result<Option<String>, AppError> {
+    let result: Result<(Value, bool, i64), _> = conn.query_row(
+        "SELECT api_response, compressed, cached_at FROM api_cache
+         WHERE namespace = ? AND file_hash = ?",
+        params![namespace, file_hash],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
     );
 
-    Ok(result.ok())
+    match (result.ok(), ttl_seconds) {
+        (Some((_, _, cached_at)), Some(ttl)) if cached_at < expiry_cutoff(ttl) => {
+            conn.execute(
+                "DELETE FROM api_cache WHERE namespace = ? AND file_hash = ?",
+                params![namespace, file_hash],
+            )?;
+            Ok(None)
+        }
+        (Some((response, compressed, _)), _) => {
+            conn.execute(
+                "UPDATE api_cache SET last_accessed = ? WHERE namespace = ? AND file_hash = ?",
+                params![now_secs(), namespace, file_hash],
+            )?;
+            Ok(Some(decode_response(response, compressed)?))
+        }
+        (None, _) => Ok(None),
+    }
+}
+
+/// Rows of namespace `?1` older than the cutoff `?2`.
+const EXPIRED_SELECTION: &str =
+    "SELECT rowid FROM api_cache WHERE namespace = ?1 AND cached_at < ?2";
+
+/// The `?2` least-recently-accessed rows of namespace `?1`.
+const LRU_SELECTION: &str = "SELECT rowid FROM api_cache WHERE namespace = ?1
+     ORDER BY last_accessed ASC, cached_at ASC LIMIT ?2";
+
+/// Deletes the rows `selection` picks, or with `dry_run` only counts them. Both
+/// paths run the same query so a preview always matches the real deletion.
+fn delete_selected(
+    conn: &Connection,
+    selection: &str,
+    namespace: &str,
+    param: i64,
+    dry_run: bool,
+) -> Result<usize, AppError> {
+    if dry_run {
+        let count: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM ({})", selection),
+            params![namespace, param],
+            |row| row.get(0),
+        )?;
+        return Ok(count as usize);
+    }
+    let removed = conn.execute(
+        &format!("DELETE FROM api_cache WHERE rowid IN ({})", selection),
+        params![namespace, param],
+    )?;
+    Ok(removed)
+}
+
+/// Deletes entries older than `ttl_seconds`. With `dry_run`, returns how many
+/// would be removed without touching them.
+#[tauri::command]
+pub async fn prune_expired_cache(
+    app: tauri::AppHandle,
+    ttl_seconds: u64,
+    dry_run: Option<bool>,
+    cache_namespace: Option<String>,
+) -> Result<usize, AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    with_conn(&app, |conn| {
+        delete_selected(
+            conn,
+            EXPIRED_SELECTION,
+            &namespace,
+            expiry_cutoff(ttl_seconds),
+            dry_run.unwrap_or(false),
+        )
+    })
 }
 
 #[tauri::command]
@@ -54,29 +383,994 @@ pub async fn save_cached_result(
     file_path: String,
     api_response: String,
     backend_url: String,
+    cache_namespace: Option<String>,
 ) -> Result<(), AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    let audit = settings::get_bool(&app, settings::CACHE_AUDIT_LOG, true);
+    let entry = CacheEntry { file_hash, file_path, api_response, backend_url };
+    with_conn(&app, |conn| save_entries(conn, &namespace, &[entry], audit))?;
+    Ok(())
+}
+
+const AUDIT_INSERT: &str = "INSERT INTO cache_audit_log
+    (timestamp, namespace, file_hash, backend_url, action) VALUES (?, ?, ?, ?, ?)";
+
+/// Most recent `limit` cache writes, newest first. Empty when auditing has
+/// always been off.
+#[tauri::command]
+pub async fn get_cache_audit(
+    app: tauri::AppHandle,
+    limit: usize,
+) -> Result<Vec<CacheAuditEntry>, AppError> {
+    with_conn(&app, |conn| recent_audit(conn, limit))
+}
+
+fn recent_audit(conn: &Connection, limit: usize) -> Result<Vec<CacheAuditEntry>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, namespace, file_hash, backend_url, action FROM cache_audit_log
+         ORDER BY id DESC LIMIT ?",
+    )?;
+    let entries = stmt
+        .query_map(params![limit as i64], |row| {
+            Ok(CacheAuditEntry {
+                timestamp: row.get(0)?,
+                namespace: row.get(1)?,
+                file_hash: row.get(2)?,
+                backend_url: row.get(3)?,
+                action: row.get(4)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(entries)
+}
+
+/// Saves all entries in one transaction: either every row commits or none do.
+#[tauri::command]
+pub async fn save_cached_results_batch(
+    app: tauri::AppHandle,
+    entries: Vec<CacheEntry>,
+    cache_namespace: Option<String>,
+) -> Result<usize, AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    let audit = settings::get_bool(&app, settings::CACHE_AUDIT_LOG, true);
+    with_conn(&app, |conn| save_entries(conn, &namespace, &entries, audit))
+}
+
+/// Upserts `entries` in one transaction, logging each write when `audit` is on.
+pub(crate) fn save_entries(
+    conn: &mut Connection,
+    namespace: &str,
+    entries: &[CacheEntry],
+    audit: bool,
+) -> Result<usize, AppError> {
+    let now = now_secs();
+    let tx = conn.transaction()?;
+    {
+        let mut audit_stmt = tx.prepare(AUDIT_INSERT)?;
+        let mut stmt = tx.prepare(
+            "INSERT OR REPLACE INTO api_cache
+                (namespace, file_hash, file_path, api_response, backend_url, cached_at, compressed, last_accessed)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )?;
+        for entry in entries {
+            let (response, compressed) = encode_response(&entry.api_response)?;
+            stmt.execute(params![
+                namespace,
+                entry.file_hash,
+                entry.file_path,
+                response,
+                entry.backend_url,
+                now,
+                compressed,
+                now
+            ])?;
+            if audit {
+                audit_stmt.execute(params![now, namespace, entry.file_hash, entry.backend_url, "save"])?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(entries.len())
+}
+
+/// Deletes the least-recently-accessed rows until at most `max_entries` remain.
+/// With `dry_run`, returns how many would be removed without touching them.
+#[tauri::command]
+pub async fn evict_cache(
+    app: tauri::AppHandle,
+    max_entries: usize,
+    dry_run: Option<bool>,
+    cache_namespace: Option<String>,
+) -> Result<usize, AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    with_conn(&app, |conn| {
+        evict_lru(conn, &namespace, max_entries, dry_run.unwrap_or(false))
+    })
+}
+
+fn evict_lru(
+    conn: &Connection,
+    namespace: &str,
+    max_entries: usize,
+    dry_run: bool,
+) -> Result<usize, AppError> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM api_cache WHERE namespace = ?",
+        params![namespace],
+        |row| row.get(0),
+    )?;
+    let excess = count - max_entries as i64;
+    if excess <= 0 {
+        return Ok(0);
+    }
+    delete_selected(conn, LRU_SELECTION, namespace, excess, dry_run)
+}
+
+/// Bytes charged per row on top of its response for keys, paths and page overhead.
+const ROW_OVERHEAD_BYTES: i64 = 256;
+
+/// Deletes least-recently-accessed rows, across all namespaces since they
+/// share one file, until the responses plus a fixed per-row overhead fit in
+/// `max_bytes`. With `vacuum`, compacts the file
+/// afterwards so the freed space goes back to the disk. Returns rows evicted.
+#[tauri::command]
+pub async fn enforce_cache_size_limit(
+    app: tauri::AppHandle,
+    max_bytes: u64,
+    vacuum: Option<bool>,
+) -> Result<usize, AppError> {
+    with_conn(&app, |conn| shrink_to(conn, max_bytes, vacuum.unwrap_or(false)))
+}
+
+fn shrink_to(conn: &mut Connection, max_bytes: u64, vacuum: bool) -> Result<usize, AppError> {
+    let rows: Vec<(i64, i64)> = {
+        let mut stmt = conn.prepare(
+            "SELECT rowid, LENGTH(CAST(api_response AS BLOB)) + ?1 FROM api_cache
+             ORDER BY last_accessed ASC, cached_at ASC",
+        )?;
+        let rows =
+            stmt.query_map(params![ROW_OVERHEAD_BYTES], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut total: i64 = rows.iter().map(|(_, size)| size).sum();
+    let limit = i64::try_from(max_bytes).unwrap_or(i64::MAX);
+    let evicted: Vec<i64> = rows
+        .iter()
+        .take_while(|(_, size)| {
+            let over = total > limit;
+            if over {
+                total -= size;
+            }
+            over
+        })
+        .map(|(rowid, _)| *rowid)
+        .collect();
+
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("DELETE FROM api_cache WHERE rowid = ?")?;
+        for rowid in &evicted {
+            stmt.execute(params![rowid])?;
+        }
+    }
+    tx.commit()?;
+
+    if vacuum && !evicted.is_empty() {
+        conn.execute("VACUUM", [])?;
+    }
+    Ok(evicted.len())
+}
+
+/// Deletes every cached response in the namespace and compacts the database file.
+#[tauri::command]
+pub async fn clear_cache(
+    app: tauri::AppHandle,
+    cache_namespace: Option<String>,
+) -> Result<usize, AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    with_conn(&app, |conn| clear_namespace(conn, &namespace))
+}
+
+fn clear_namespace(conn: &Connection, namespace: &str) -> Result<usize, AppError> {
+    let removed = conn.execute("DELETE FROM api_cache WHERE namespace = ?", params![namespace])?;
+    conn.execute("VACUUM", [])?;
+    Ok(removed)
+}
+
+/// Deletes rows whose `file_path` no longer exists on disk, e.g. after the
+/// file was deleted or moved, in every namespace. Paths are checked outside
+/// the transaction; the deletions then commit together.
+#[tauri::command]
+pub async fn prune_orphaned_cache(app: tauri::AppHandle) -> Result<usize, AppError> {
+    with_conn(&app, prune_orphans)
+}
+
+fn prune_orphans(conn: &mut Connection) -> Result<usize, AppError> {
+    let rows: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT rowid, file_path FROM api_cache")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let orphans: Vec<i64> = rows
+        .into_par_iter()
+        .filter(|(_, file_path)| !Path::new(file_path).exists())
+        .map(|(rowid, _)| rowid)
+        .collect();
+
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("DELETE FROM api_cache WHERE rowid = ?")?;
+        for rowid in &orphans {
+            stmt.execute(params![rowid])?;
+        }
+    }
+    tx.commit()?;
+    Ok(orphans.len())
+}
+
+/// Drops every response produced by `backend_url`, e.g. after a model redeploy.
+#[tauri::command]
+pub async fn invalidate_cache_for_backend(
+    app: tauri::AppHandle,
+    backend_url: String,
+    cache_namespace: Option<String>,
+) -> Result<usize, AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    with_conn(&app, |conn| delete_backend_rows(conn, &namespace, &backend_url))
+}
+
+fn delete_backend_rows(
+    conn: &Connection,
+    namespace: &str,
+    backend_url: &str,
+) -> Result<usize, AppError> {
+    let removed = conn.execute(
+        "DELETE FROM api_cache WHERE namespace = ? AND backend_url = ?",
+        params![namespace, backend_url],
+    )?;
+    Ok(removed)
+}
+
+/// Newest `limit` entries from `backend_url`, for inspecting what one backend
+/// returned. Bodies are omitted to keep the payload small, except for the
+/// entry whose hash is `include_body_for`.
+#[tauri::command]
+pub async fn list_cache_by_backend(
+    app: tauri::AppHandle,
+    backend_url: String,
+    limit: usize,
+    include_body_for: Option<String>,
+    cache_namespace: Option<String>,
+) -> Result<Vec<BackendCacheEntry>, AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    with_conn(&app, |conn| {
+        backend_entries(conn, &namespace, &backend_url, limit, include_body_for.as_deref())
+    })
+}
+
+fn backend_entries(
+    conn: &Connection,
+    namespace: &str,
+    backend_url: &str,
+    limit: usize,
+    include_body_for: Option<&str>,
+) -> Result<Vec<BackendCacheEntry>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT file_hash, file_path, cached_at, length(CAST(api_response AS BLOB)), compressed,
+                CASE WHEN file_hash = ?3 THEN api_response END
+         FROM api_cache WHERE namespace = ?1 AND backend_url = ?2
+         ORDER BY cached_at DESC LIMIT ?4",
+    )?;
+    let rows = stmt.query_map(
+        params![namespace, backend_url, include_body_for, limit as i64],
+        |row| {
+            let compressed: bool = row.get(4)?;
+            let body: Option<Value> = row.get(5)?;
+            Ok((
+                BackendCacheEntry {
+                    file_hash: row.get(0)?,
+                    file_path: row.get(1)?,
+                    cached_at: row.get(2)?,
+                    response_bytes: row.get::<_, i64>(3)? as u64,
+                    compressed,
+                    api_response: None,
+                },
+                body,
+            ))
+        },
+    )?;
+
+    rows.map(|row| {
+        let (mut entry, body) = row?;
+        entry.api_response =
+            body.map(|body| decode_response(body, entry.compressed)).transpose()?;
+        Ok(entry)
+    })
+    .collect()
+}
+
+/// Checks the cache db for damage: SQLite's own `integrity_check`, then every
+/// `api_cache` row for an empty or non-JSON response. Only reports; deleting
+/// bad rows is left to the user.
+#[tauri::command]
+pub async fn verify_cache(app: tauri::AppHandle) -> Result<CacheIntegrity, AppError> {
+    with_conn(&app, |conn| check_integrity(conn))
+}
+
+fn check_integrity(conn: &Connection) -> Result<CacheIntegrity, AppError> {
+    let integrity_messages = conn
+        .prepare("PRAGMA integrity_check")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut report = CacheIntegrity {
+        integrity_ok: integrity_messages.len() == 1 && integrity_messages[0] == "ok",
+        integrity_messages,
+        row_count: 0,
+        empty_responses: Vec::new(),
+        malformed_responses: Vec::new(),
+    };
+
+    let mut stmt = conn.prepare("SELECT file_hash, api_response, compressed FROM api_cache")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        report.row_count += 1;
+        let file_hash: String = row.get(0)?;
+        let response = decode_response(row.get(1)?, row.get(2)?);
+        match response {
+            Ok(body) if body.trim().is_empty() => report.empty_responses.push(file_hash),
+            Ok(body) if serde_json::from_str::<serde_json::Value>(&body).is_ok() => {}
+            _ => report.malformed_responses.push(file_hash),
+        }
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn cache_stats(
+    app: tauri::AppHandle,
+    cache_namespace: Option<String>,
+) -> Result<CacheStats, AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    with_conn(&app, |conn| namespace_stats(conn, &namespace))
+}
+
+fn namespace_stats(conn: &Connection, namespace: &str) -> Result<CacheStats, AppError> {
+    let stats = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(LENGTH(CAST(api_response AS BLOB))), 0),
+                MIN(cached_at), MAX(cached_at), COUNT(DISTINCT backend_url)
+         FROM api_cache WHERE namespace = ?",
+        params![namespace],
+        |row| {
+            Ok(CacheStats {
+                entry_count: row.get::<_, i64>(0)? as u64,
+                total_response_bytes: row.get::<_, i64>(1)? as u64,
+                oldest_cached_at: row.get(2)?,
+                newest_cached_at: row.get(3)?,
+                backend_count: row.get::<_, i64>(4)? as u64,
+            })
+        },
+    )?;
+    Ok(stats)
+}
+
+/// Writes every `api_cache` row to `output_path` as a JSON array of
+/// `CacheExportRow`, decompressing responses so the file is self-contained.
+/// Rows are streamed to disk one at a time. Returns the number written.
+#[tauri::command]
+pub async fn export_cache(app: tauri::AppHandle, output_path: String) -> Result<usize, AppError> {
+    let path = Path::new(&output_path);
+    let file = std::fs::File::create(path).at_path(path)?;
+    let mut out = BufWriter::new(file);
+
+    let count = with_conn(&app, |conn| write_export(conn, &mut out))?;
+    out.flush().at_path(path)?;
+    Ok(count)
+}
+
+/// Streams every `api_cache` row to `out` as a JSON array, one row per line.
+fn write_export(conn: &Connection, out: &mut impl Write) -> Result<usize, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT namespace, file_hash, file_path, api_response, compressed, backend_url, cached_at
+         FROM api_cache ORDER BY cached_at",
+    )?;
+    let mut rows = stmt.query([])?;
+
+    out.write_all(b"[")?;
+    let mut count = 0;
+    while let Some(row) = rows.next()? {
+        let entry = CacheExportRow {
+            namespace: row.get(0)?,
+            file_hash: row.get(1)?,
+            file_path: row.get(2)?,
+            api_response: decode_response(row.get(3)?, row.get(4)?)?,
+            backend_url: row.get(5)?,
+            cached_at: row.get(6)?,
+        };
+        if count > 0 {
+            out.write_all(b",")?;
+        }
+        out.write_all(b"\n")?;
+        serde_json::to_writer(&mut *out, &entry).map_err(|e| AppError::IoError(e.to_string()))?;
+        count += 1;
+    }
+    out.write_all(b"\n]\n")?;
+    Ok(count)
+}
+
+/// Loads a file written by `export_cache` and upserts its rows in one
+/// transaction, keeping their original `cached_at`. The whole file is parsed
+/// and validated first, so malformed input imports nothing.
+#[tauri::command]
+pub async fn import_cache(app: tauri::AppHandle, input_path: String) -> Result<usize, AppError> {
+    let rows = read_export(Path::new(&input_path))?;
+    with_conn(&app, |conn| import_rows(conn, &rows))
+}
+
+fn read_export(path: &Path) -> Result<Vec<CacheExportRow>, AppError> {
+    let file = std::fs::File::open(path).at_path(path)?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| AppError::InvalidFile(format!("JSON de cache invalido ({})", e)).at_path(path))
+}
+
+fn import_rows(conn: &mut Connection, rows: &[CacheExportRow]) -> Result<usize, AppError> {
+    let now = now_secs();
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT OR REPLACE INTO api_cache
+                (namespace, file_hash, file_path, api_response, backend_url, cached_at, compressed, last_accessed)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )?;
+        for row in rows {
+            let (response, compressed) = encode_response(&row.api_response)?;
+            stmt.execute(params![
+                row.namespace,
+                row.file_hash,
+                row.file_path,
+                response,
+                row.backend_url,
+                row.cached_at,
+                compressed,
+                now
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(rows.len())
+}
+
+/// Total and free space on the volume that holds the cache db, so the UI can
+/// warn before a large run fills the disk.
+#[tauri::command]
+pub async fn cache_disk_space(app: tauri::AppHandle) -> Result<DiskSpace, AppError> {
     let db_path = get_db_path(&app);
-    let conn = Connection::open(&db_path)?;
+    let dir = db_path.parent().unwrap_or(&db_path);
+    let unavailable = |e: std::io::Error| {
+        AppError::IoError(format!("volume do cache inacessivel: {} ({})", dir.display(), e))
+    };
 
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    let total_bytes = fs2::total_space(dir).map_err(unavailable)?;
+    let available_bytes = fs2::available_space(dir).map_err(unavailable)?;
+    let available_percent = if total_bytes == 0 {
+        0.0
+    } else {
+        available_bytes as f64 / total_bytes as f64 * 100.0
+    };
 
-    conn.execute(
-        "INSERT OR REPLACE INTO api_cache (file_hash, file_path, api_response, backend_url, cached_at)
+    Ok(DiskSpace {
+        total_bytes,
+        available_bytes,
+        available_percent,
+    })
+}
+
+/// Stores the text extracted from the file with `file_hash`. Keyed by content,
+/// so a moved or renamed file still hits.
+#[tauri::command]
+pub async fn save_extracted_text(
+    app: tauri::AppHandle,
+    file_hash: String,
+    text: String,
+) -> Result<(), AppError> {
+    store_extracted_text(&app, &file_hash, &text)
+}
+
+#[tauri::command]
+pub async fn get_extracted_text(
+    app: tauri::AppHandle,
+    file_hash: String,
+) -> Result<Option<String>, AppError> {
+    load_extracted_text(&app, &file_hash)
+}
+
+/// Caches the aggregate result of analyzing `folder_path` as a unit, under the
+/// folder's current `hash_folder` digest, which is returned. Earlier results
+/// for the same folder are replaced.
+#[tauri::command]
+pub async fn cache_folder_result(
+    app: tauri::AppHandle,
+    folder_path: String,
+    result_json: String,
+) -> Result<String, AppError> {
+    let folder_hash = hash_folder(folder_path.clone()).await?;
+    with_conn(&app, |conn| store_folder_result(conn, &folder_hash, &folder_path, &result_json))?;
+    Ok(folder_hash)
+}
+
+fn store_folder_result(
+    conn: &mut Connection,
+    folder_hash: &str,
+    folder_path: &str,
+    result_json: &str,
+) -> Result<(), AppError> {
+    let (stored, compressed) = encode_response(result_json)?;
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM folder_cache WHERE folder_path = ?", params![folder_path])?;
+    tx.execute(
+        "INSERT OR REPLACE INTO folder_cache
+         (folder_hash, folder_path, result, compressed, cached_at)
          VALUES (?, ?, ?, ?, ?)",
-        params![file_hash, file_path, api_response, backend_url, now as i64],
+        params![folder_hash, folder_path, stored, compressed, now_secs()],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// The cached result for `folder_path`, if one was stored while the folder
+/// had exactly its current contents. Adding, removing, renaming or editing
+/// any PDF changes the folder hash, so the stale result is a miss.
+#[tauri::command]
+pub async fn get_folder_result(
+    app: tauri::AppHandle,
+    folder_path: String,
+) -> Result<Option<String>, AppError> {
+    let folder_hash = hash_folder(folder_path).await?;
+    with_conn(&app, |conn| folder_result(conn, &folder_hash))
+}
+
+fn folder_result(conn: &Connection, folder_hash: &str) -> Result<Option<String>, AppError> {
+    let row: Option<(Value, bool)> = conn
+        .query_row(
+            "SELECT result, compressed FROM folder_cache WHERE folder_hash = ?",
+            params![folder_hash],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    row.map(|(result, compressed)| decode_response(result, compressed)).transpose()
+}
+
+pub(crate) fn store_extracted_text(
+    app: &tauri::AppHandle,
+    file_hash: &str,
+    text: &str,
+) -> Result<(), AppError> {
+    with_conn(app, |conn| write_extracted_text(conn, file_hash, text))
+}
+
+pub(crate) fn load_extracted_text(
+    app: &tauri::AppHandle,
+    file_hash: &str,
+) -> Result<Option<String>, AppError> {
+    with_conn(app, |conn| read_extracted_text(conn, file_hash))
+}
+
+fn write_extracted_text(conn: &Connection, file_hash: &str, text: &str) -> Result<(), AppError> {
+    let (stored, compressed) = encode_response(text)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO extracted_text (file_hash, text, compressed, extracted_at)
+         VALUES (?, ?, ?, ?)",
+        params![file_hash, stored, compressed, now_secs()],
     )?;
+    Ok(())
+}
 
+fn read_extracted_text(conn: &Connection, file_hash: &str) -> Result<Option<String>, AppError> {
+    let row: Option<(Value, bool)> = conn
+        .query_row(
+            "SELECT text, compressed FROM extracted_text WHERE file_hash = ?",
+            params![file_hash],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    row.map(|(text, compressed)| decode_response(text, compressed)).transpose()
+}
+
+/// Hashes of every file with stored extracted text.
+pub(crate) fn extracted_hashes(app: &tauri::AppHandle) -> Result<HashSet<String>, AppError> {
+    with_conn(app, |conn| {
+        let mut stmt = conn.prepare("SELECT file_hash FROM extracted_text")?;
+        let hashes = stmt.query_map([], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        Ok(hashes)
+    })
+}
+
+/// Cached PNG for `file_hash` rendered at `max_dimension`, if any.
+pub(crate) fn get_thumbnail(
+    app: &tauri::AppHandle,
+    file_hash: &str,
+    max_dimension: u32,
+) -> Result<Option<Vec<u8>>, AppError> {
+    with_conn(app, |conn| {
+        let png = conn
+            .query_row(
+                "SELECT png FROM thumbnails WHERE file_hash = ? AND max_dimension = ?",
+                params![file_hash, max_dimension],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(png)
+    })
+}
+
+pub(crate) fn save_thumbnail(
+    app: &tauri::AppHandle,
+    file_hash: &str,
+    max_dimension: u32,
+    png: &[u8],
+) -> Result<(), AppError> {
+    with_conn(app, |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO thumbnails (file_hash, max_dimension, png, created_at)
+             VALUES (?, ?, ?, ?)",
+            params![file_hash, max_dimension, png, now_secs()],
+        )?;
+        Ok(())
+    })
+}
+
+/// Entries kept in `recent_files`; older ones are pruned on each access.
+const RECENT_FILES_CAP: i64 = 200;
+
+/// Marks `file_path` as just opened, for the recent documents list.
+#[tauri::command]
+pub async fn record_file_access(app: tauri::AppHandle, file_path: String) -> Result<(), AppError> {
+    with_conn(&app, |conn| record_access(conn, &file_path))
+}
+
+fn record_access(conn: &mut Connection, file_path: &str) -> Result<(), AppError> {
+    let tx = conn.transaction()?;
+    // REPLACE gives the row a new rowid, which orders opens within the same second.
+    tx.execute(
+        "INSERT OR REPLACE INTO recent_files (path, last_opened) VALUES (?, ?)",
+        params![file_path, now_secs()],
+    )?;
+    tx.execute(
+        "DELETE FROM recent_files WHERE path NOT IN (
+            SELECT path FROM recent_files ORDER BY last_opened DESC, rowid DESC LIMIT ?
+         )",
+        params![RECENT_FILES_CAP],
+    )?;
+    tx.commit()?;
     Ok(())
 }
 
+/// Up to `limit` recently opened files, newest first. Files that no longer
+/// exist are skipped but kept in the table in case they come back.
+#[tauri::command]
+pub async fn get_recent_files(
+    app: tauri::AppHandle,
+    limit: usize,
+) -> Result<Vec<RecentFile>, AppError> {
+    let recent = with_conn(&app, |conn| recent_rows(conn))?;
+    Ok(existing_recent(recent, limit))
+}
+
+/// Every recorded open, newest first.
+fn recent_rows(conn: &Connection) -> Result<Vec<(String, i64)>, AppError> {
+    let rows = conn
+        .prepare(
+            "SELECT path, last_opened FROM recent_files
+             ORDER BY last_opened DESC, rowid DESC",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    Ok(rows)
+}
+
+/// The first `limit` of `recent` whose file still exists.
+fn existing_recent(recent: Vec<(String, i64)>, limit: usize) -> Vec<RecentFile> {
+    recent
+        .into_iter()
+        .filter(|(path, _)| Path::new(path).is_file())
+        .take(limit)
+        .map(|(path, last_opened)| RecentFile {
+            name: Path::new(&path).file_name().unwrap_or_default().to_string_lossy().to_string(),
+            path,
+            last_opened,
+        })
+        .collect()
+}
+
+/// Points every cache row recorded for `from` at `to` after the file moved,
+/// in all namespaces. Returns how many rows changed.
+pub(crate) fn update_cached_path(
+    app: &tauri::AppHandle,
+    from: &str,
+    to: &str,
+) -> Result<usize, AppError> {
+    with_conn(app, |conn| {
+        let updated = conn.execute(
+            "UPDATE api_cache SET file_path = ? WHERE file_path = ?",
+            params![to, from],
+        )?;
+        Ok(updated)
+    })
+}
+
+/// Weight of the newest batch in the rolling pages-per-second average.
+const RATE_SMOOTHING: f64 = 0.3;
+
+/// Rolling pages-per-second average and how many batches it covers, if any
+/// batch has been recorded.
+pub(crate) fn extraction_rate(app: &tauri::AppHandle) -> Result<Option<(f64, u32)>, AppError> {
+    with_conn(app, |conn| {
+        let rate = conn
+            .query_row(
+                "SELECT pages_per_second, samples FROM extraction_stats WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(rate)
+    })
+}
+
+/// Folds a finished batch's throughput into the rolling average.
+pub(crate) fn record_extraction_rate(
+    app: &tauri::AppHandle,
+    pages: usize,
+    elapsed: Duration,
+) -> Result<(), AppError> {
+    let seconds = elapsed.as_secs_f64();
+    if pages == 0 || seconds <= 0.0 {
+        return Ok(());
+    }
+    let observed = pages as f64 / seconds;
+    let (rate, samples) = match extraction_rate(app)? {
+        Some((average, samples)) => {
+            (average * (1.0 - RATE_SMOOTHING) + observed * RATE_SMOOTHING, samples + 1)
+        }
+        None => (observed, 1),
+    };
+    with_conn(app, |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO extraction_stats (id, pages_per_second, samples, updated_at)
+             VALUES (1, ?, ?, ?)",
+            params![rate, samples, now_secs()],
+        )?;
+        Ok(())
+    })
+}
+
+/// Hex digest of the file. SHA-256 (the default) is unprefixed for compatibility
+/// with existing cache keys; BLAKE3 digests are returned as `blake3:<hex>` so the
+/// two schemes never collide in `api_cache`.
+#[tauri::command]
+pub async fn hash_file(
+    file_path: String,
+    hash_algorithm: Option<HashAlgorithm>,
+) -> Result<String, AppError> {
+    hash_path(Path::new(&file_path), hash_algorithm.unwrap_or_default())
+}
+
+/// Hashes many files across the rayon pool, preserving input order. A file that
+/// can't be hashed yields `error:<message>` in place of its digest.
+#[tauri::command]
+pub async fn hash_files(
+    paths: Vec<String>,
+    hash_algorithm: Option<HashAlgorithm>,
+) -> Result<Vec<(String, String)>, AppError> {
+    let algorithm = hash_algorithm.unwrap_or_default();
+    let hashes = paths
+        .into_par_iter()
+        .map(|path| {
+            let hash = hash_path(Path::new(&path), algorithm)
+                .unwrap_or_else(|e| format!("error:{}", e));
+            (path, hash)
+        })
+        .collect();
+    Ok(hashes)
+}
+
+/// SQLite's default cap on bound parameters is 999; keep each `IN` list well under it.
+const LOOKUP_CHUNK: usize = 500;
+
+/// Of `file_paths`, the ones with no cached response in the namespace, in
+/// input order. Files are SHA-256 hashed in parallel and looked up in chunks
+/// with an `IN` query. A file that can't be hashed counts as uncached, so the
+/// backend call reports the actual error.
+#[tauri::command]
+pub async fn filter_uncached(
+    app: tauri::AppHandle,
+    file_paths: Vec<String>,
+    cache_namespace: Option<String>,
+) -> Result<Vec<String>, AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    let hashed: Vec<(String, Option<String>)> = file_paths
+        .into_par_iter()
+        .map(|path| {
+            let hash = hash_path(Path::new(&path), HashAlgorithm::Sha256).ok();
+            (path, hash)
+        })
+        .collect();
+
+    with_conn(&app, |conn| uncached_paths(conn, &namespace, hashed))
+}
+
+/// Paths from `hashed` whose hash has no row in `namespace`. Files that
+/// couldn't be hashed count as uncached.
+fn uncached_paths(
+    conn: &Connection,
+    namespace: &str,
+    hashed: Vec<(String, Option<String>)>,
+) -> Result<Vec<String>, AppError> {
+    let hashes: Vec<&str> = hashed.iter().filter_map(|(_, hash)| hash.as_deref()).collect();
+    let mut cached = HashSet::new();
+    for chunk in hashes.chunks(LOOKUP_CHUNK) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT file_hash FROM api_cache WHERE namespace = ? AND file_hash IN ({})",
+            placeholders
+        ))?;
+        let params = std::iter::once(namespace).chain(chunk.iter().copied());
+        let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+            row.get::<_, String>(0)
+        })?;
+        for hash in rows {
+            cached.insert(hash?);
+        }
+    }
+
+    Ok(hashed
+        .into_iter()
+        .filter(|(_, hash)| hash.as_ref().is_none_or(|hash| !cached.contains(hash)))
+        .map(|(path, _)| path)
+        .collect())
+}
+
+/// Composite hash of every PDF under `folder_path`: each file's path relative
+/// to the folder and its SHA-256, sorted by path, hashed together. Any added,
+/// removed, renamed or edited PDF changes it; moving the whole folder doesn't.
+/// Returned as `folder:<hex>` so it can't collide with a file digest.
 #[tauri::command]
-pub async fn hash_file(file_path: String) -> Result<String, AppError> {
-    let mut file = std::fs::File::open(&file_path)?;
+pub async fn hash_folder(folder_path: String) -> Result<String, AppError> {
+    let folder = Path::new(&folder_path);
+    if !folder.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+
+    let mut entries = filesystem::find_pdfs(folder)?
+        .into_par_iter()
+        .map(|pdf| {
+            let path = PathBuf::from(&pdf.full_path);
+            let hash = hash_path(&path, HashAlgorithm::Sha256)?;
+            let relative = path.strip_prefix(folder).unwrap_or(&path).to_string_lossy().to_string();
+            Ok((relative, hash))
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+    entries.sort();
+
+    let mut hasher = Sha256::new();
+    for (relative, hash) in &entries {
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update(hash.as_bytes());
+        hasher.update([b'\n']);
+    }
+    Ok(format!("folder:{:x}", hasher.finalize()))
+}
+
+pub(crate) fn hash_path(path: &Path, algorithm: HashAlgorithm) -> Result<String, AppError> {
+    match algorithm {
+        HashAlgorithm::Sha256 => sha256_file(path),
+        HashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_mmap_rayon(path).at_path(path)?;
+            Ok(format!("blake3:{}", hasher.finalize().to_hex()))
+        }
+    }
+}
+
+/// Bytes read from each end of the file by `quick_hash_file`.
+const QUICK_HASH_WINDOW: u64 = 64 * 1024;
+
+/// Cheap change-detection hash over the file size plus its first and last 64 KB,
+/// returned as `quick:<hex>` so it can never be mistaken for a full digest.
+///
+/// This is probabilistic: an edit confined to the middle of a large file that
+/// keeps the size and both ends intact goes unnoticed. Use `hash_file` when
+/// the result must identify the content exactly.
+#[tauri::command]
+pub async fn quick_hash_file(file_path: String) -> Result<String, AppError> {
+    let path = Path::new(&file_path);
+    let mut file = std::fs::File::open(path).at_path(path)?;
+    let len = file.metadata().at_path(path)?.len();
+    let mut hasher = Sha256::new();
+    hasher.update(len.to_le_bytes());
+
+    let mut head = Vec::new();
+    (&mut file).take(QUICK_HASH_WINDOW).read_to_end(&mut head).at_path(path)?;
+    hasher.update(&head);
+
+    if len > QUICK_HASH_WINDOW {
+        let tail_start = len.saturating_sub(QUICK_HASH_WINDOW).max(QUICK_HASH_WINDOW);
+        file.seek(SeekFrom::Start(tail_start)).at_path(path)?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail).at_path(path)?;
+        hasher.update(&tail);
+    }
+
+    Ok(format!("quick:{:x}", hasher.finalize()))
+}
+
+/// Files at least this large are memory-mapped and fed to the hasher in one
+/// update. Below it, setting up the mapping costs more than the read syscalls it
+/// saves. Best of 20 SHA-256 runs over a warm page cache (`mmap_crossover`,
+/// release build, x86_64 Linux):
+///
+/// | size   | stream    | mmap      |
+/// |--------|-----------|-----------|
+/// | 16 KB  | 0.017 ms  | 0.019 ms  |
+/// | 64 KB  | 0.057 ms  | 0.060 ms  |
+/// | 256 KB | 0.223 ms  | 0.216 ms  |
+/// | 1 MB   | 0.897 ms  | 0.851 ms  |
+/// | 4 MB   | 3.59 ms   | 3.33 ms   |
+/// | 16 MB  | 14.8 ms   | 13.0 ms   |
+/// | 64 MB  | 58.2 ms   | 48.8 ms   |
+/// | 256 MB | 228 ms    | 208 ms    |
+///
+/// The two tie around 256 KB; from 1 MB up the mapping wins every run.
+const MMAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// Largest read buffer used when streaming a file into the hasher.
+const MAX_HASH_BUFFER: usize = 1024 * 1024;
+
+/// Read size for a file of `len` bytes: up to 1 MB a single read into a buffer
+/// of exactly the file's size, beyond that 1 MB chunks. Since files of
+/// `MMAP_THRESHOLD` and up are mapped, the chunked case only runs when the
+/// mapping fails (some network and FUSE mounts), and there it cuts the read
+/// calls 128-fold compared with the old fixed 8 KB buffer. On a warm page
+/// cache SHA-256 itself is the bottleneck (`buffer_sizes`, best of 20):
+///
+/// | size   | 8 KB     | adaptive |
+/// |--------|----------|----------|
+/// | 16 KB  | 0.017 ms | 0.017 ms |
+/// | 256 KB | 0.223 ms | 0.223 ms |
+/// | 1 MB   | 0.885 ms | 0.895 ms |
+/// | 64 MB  | 61.2 ms  | 59.4 ms  |
+/// | 256 MB | 240 ms   | 236 ms   |
+///
+/// so the saving is the syscalls, worth most where each read is a round trip.
+fn hash_buffer_size(len: u64) -> usize {
+    (len as usize).clamp(1, MAX_HASH_BUFFER)
+}
+
+fn sha256_file(path: &Path) -> Result<String, AppError> {
+    let file = std::fs::File::open(path).at_path(path)?;
+    let len = file.metadata().at_path(path)?.len();
+    if len >= MMAP_THRESHOLD {
+        // If the mapping can't be created we fall back to streaming the same handle.
+        if let Some(digest) = sha256_mapped(&file) {
+            return Ok(digest);
+        }
+    }
+    sha256_stream(file, hash_buffer_size(len)).at_path(path)
+}
+
+/// Hashes the whole file through a read-only mapping, or `None` if it can't be mapped.
+fn sha256_mapped(file: &std::fs::File) -> Option<String> {
+    // SAFETY: the mapping is read-only and dropped before returning.
+    let map = unsafe { memmap2::Mmap::map(file) }.ok()?;
+    let mut hasher = Sha256::new();
+    hasher.update(&map[..]);
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+fn sha256_stream(mut file: std::fs::File, buffer_size: usize) -> Result<String, AppError> {
     let mut hasher = Sha256::new();
-    let mut buffer = [0u8; 8192];
+    let mut buffer = vec![0u8; buffer_size];
 
     loop {
         let bytes_read = file.read(&mut buffer)?;
@@ -90,22 +1384,726 @@ pub async fn hash_file(file_path: String) -> Result<String, AppError> {
 }
 
 #[tauri::command]
-pub async fn list_cache_entries(app: tauri::AppHandle) -> Result<Vec<serde_json::Value>, AppError> {
-    let db_path = get_db_path(&app);
-    let conn = Connection::open(&db_path)?;
+pub async fn list_cache_entries(
+    app: tauri::AppHandle,
+    cache_namespace: Option<String>,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let namespace = namespace_or_default(cache_namespace);
+    with_conn(&app, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT file_hash, file_path, cached_at FROM api_cache
+             WHERE namespace = ? ORDER BY cached_at DESC LIMIT 50"
+        )?;
 
-    let mut stmt = conn.prepare(
-        "SELECT file_hash, file_path, cached_at FROM api_cache ORDER BY cached_at DESC LIMIT 50"
-    )?;
+        let entries = stmt.query_map(params![namespace], |row| {
+            Ok(serde_json::json!({
+                "file_hash": row.get::<_, String>(0)?,
+                "file_path": row.get::<_, String>(1)?,
+                "cached_at": row.get::<_, i64>(2)?
+            }))
+        })?;
+
+        let result: Vec<serde_json::Value> = entries.filter_map(|e| e.ok()).collect();
+        Ok(result)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Fastest of `runs` timings of `hash`, in milliseconds.
+    fn best_ms(runs: usize, mut hash: impl FnMut()) -> f64 {
+        (0..runs)
+            .map(|_| {
+                let started = Instant::now();
+                hash();
+                started.elapsed().as_secs_f64() * 1000.0
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Temp file of `len` pseudo-random bytes, already in the page cache.
+    fn scratch_file(dir: &Path, len: usize) -> PathBuf {
+        let path = dir.join(format!("{}.bin", len));
+        let bytes: Vec<u8> = (0..len).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    /// Fresh in-memory cache db at the current schema version.
+    fn cache_db() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&mut conn).unwrap();
+        conn
+    }
+
+    /// Inserts a `{}` response for `file_hash` from `backend_url`, written at `cached_at`.
+    fn insert_row(conn: &Connection, file_hash: &str, backend_url: &str, cached_at: i64) {
+        conn.execute(
+            "INSERT INTO api_cache
+                (file_hash, file_path, api_response, backend_url, cached_at, last_accessed)
+             VALUES (?1, ?2, '{}', ?3, ?4, ?4)",
+            params![file_hash, format!("/processos/{}.pdf", file_hash), backend_url, cached_at],
+        )
+        .unwrap();
+    }
+
+    fn row_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM api_cache", [], |row| row.get(0)).unwrap()
+    }
+
+    /// Every `file_hash` in `api_cache`, sorted.
+    fn cached_hashes(conn: &Connection) -> Vec<String> {
+        conn.prepare("SELECT file_hash FROM api_cache ORDER BY file_hash")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
 
-    let entries = stmt.query_map([], |row| {
-        Ok(serde_json::json!({
-            "file_hash": row.get::<_, String>(0)?,
-            "file_path": row.get::<_, String>(1)?,
-            "cached_at": row.get::<_, i64>(2)?
-        }))
-    })?;
+    #[test]
+    fn entries_past_the_ttl_expire() {
+        let conn = cache_db();
+        insert_row(&conn, "antigo", "http://backend", now_secs() - 7200);
+        insert_row(&conn, "recente", "http://backend", now_secs());
 
-    let result: Vec<serde_json::Value> = entries.filter_map(|e| e.ok()).collect();
-    Ok(result)
+        assert_eq!(cached_result(&conn, "default", "antigo", None).unwrap().as_deref(), Some("{}"));
+        assert_eq!(cached_result(&conn, "default", "antigo", Some(3600)).unwrap(), None);
+        assert_eq!(cached_result(&conn, "default", "antigo", None).unwrap(), None);
+        let fresh = cached_result(&conn, "default", "recente", Some(3600)).unwrap();
+        assert_eq!(fresh.as_deref(), Some("{}"));
+    }
+
+    #[test]
+    fn pruning_removes_only_expired_entries() {
+        let conn = cache_db();
+        insert_row(&conn, "antigo", "http://backend", now_secs() - 7200);
+        insert_row(&conn, "recente", "http://backend", now_secs());
+        let cutoff = expiry_cutoff(3600);
+
+        assert_eq!(delete_selected(&conn, EXPIRED_SELECTION, "default", cutoff, true).unwrap(), 1);
+        assert_eq!(row_count(&conn), 2);
+        assert_eq!(delete_selected(&conn, EXPIRED_SELECTION, "default", cutoff, false).unwrap(), 1);
+        assert_eq!(row_count(&conn), 1);
+    }
+
+    #[test]
+    fn invalidating_a_backend_keeps_the_others() {
+        let conn = cache_db();
+        insert_row(&conn, "a1", "http://modelo-antigo", now_secs());
+        insert_row(&conn, "a2", "http://modelo-antigo", now_secs());
+        insert_row(&conn, "b1", "http://outro-backend", now_secs());
+
+        assert_eq!(delete_backend_rows(&conn, "default", "http://modelo-antigo").unwrap(), 2);
+        let remaining: String = conn
+            .query_row("SELECT group_concat(file_hash) FROM api_cache", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, "b1");
+    }
+
+    fn entry(file_hash: &str, api_response: &str) -> CacheEntry {
+        CacheEntry {
+            file_hash: file_hash.to_string(),
+            file_path: format!("/processos/{}.pdf", file_hash),
+            api_response: api_response.to_string(),
+            backend_url: "http://backend".to_string(),
+        }
+    }
+
+    #[test]
+    fn concurrent_saves_wait_instead_of_failing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        run_migrations(&mut open_connection(&path).unwrap()).unwrap();
+
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let mut conn = open_connection(&path).unwrap();
+                    for i in 0..25 {
+                        let hash = format!("{}-{}", writer, i);
+                        save_entries(&mut conn, "default", &[entry(&hash, "{}")], true)?;
+                    }
+                    Ok::<_, AppError>(())
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+
+        let conn = open_connection(&path).unwrap();
+        assert_eq!(row_count(&conn), 200);
+        let journal_mode: String =
+            conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        let busy_timeout: i64 =
+            conn.query_row("PRAGMA busy_timeout", [], |row| row.get(0)).unwrap();
+        assert_eq!(journal_mode, "wal");
+        assert_eq!(busy_timeout, 5000);
+    }
+
+    #[test]
+    fn migrates_a_db_created_before_versioning() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE api_cache (
+                file_hash TEXT PRIMARY KEY,
+                file_path TEXT NOT NULL,
+                api_response TEXT NOT NULL,
+                backend_url TEXT NOT NULL,
+                cached_at INTEGER NOT NULL
+            );
+            INSERT INTO api_cache
+                VALUES ('abc', '/processos/a.pdf', '{\"ok\":1}', 'http://b', 100);",
+        )
+        .unwrap();
+
+        run_migrations(&mut conn).unwrap();
+
+        let version: i64 = conn
+            .query_row("SELECT MAX(version) FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+        let (namespace, last_accessed): (String, i64) = conn
+            .query_row("SELECT namespace, last_accessed FROM api_cache", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((namespace.as_str(), last_accessed), ("default", 100));
+        let cached = cached_result(&conn, "default", "abc", None).unwrap();
+        assert_eq!(cached.as_deref(), Some("{\"ok\":1}"));
+
+        // A second run finds nothing left to apply.
+        run_migrations(&mut conn).unwrap();
+        let steps: i64 =
+            conn.query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(steps, MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn large_responses_round_trip_compressed() {
+        let mut conn = cache_db();
+        let large = serde_json::json!({ "texto": "Excelentissimo Senhor Juiz ".repeat(400) })
+            .to_string();
+        assert!(large.len() > COMPRESSION_THRESHOLD);
+        let entries = [entry("grande", &large), entry("pequeno", "{}")];
+        save_entries(&mut conn, "default", &entries, false).unwrap();
+
+        let (stored_bytes, compressed): (i64, bool) = conn
+            .query_row(
+                "SELECT LENGTH(CAST(api_response AS BLOB)), compressed FROM api_cache
+                 WHERE file_hash = 'grande'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!(compressed);
+        assert!((stored_bytes as usize) < large.len() / 10);
+        assert_eq!(cached_result(&conn, "default", "grande", None).unwrap(), Some(large));
+
+        let small_compressed: bool = conn
+            .query_row("SELECT compressed FROM api_cache WHERE file_hash = 'pequeno'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert!(!small_compressed);
+    }
+
+    #[test]
+    fn eviction_removes_the_least_recently_accessed_first() {
+        let conn = cache_db();
+        let start = now_secs() - 1000;
+        for (i, hash) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            insert_row(&conn, hash, "http://backend", start + i as i64);
+        }
+        // Reading "a" makes it the most recently accessed.
+        cached_result(&conn, "default", "a", None).unwrap();
+
+        assert_eq!(evict_lru(&conn, "default", 3, true).unwrap(), 2);
+        assert_eq!(row_count(&conn), 5);
+        assert_eq!(evict_lru(&conn, "default", 3, false).unwrap(), 2);
+        assert_eq!(cached_hashes(&conn), ["a", "d", "e"]);
+        assert_eq!(evict_lru(&conn, "default", 3, false).unwrap(), 0);
+    }
+
+    async fn quick_hash(path: &Path) -> String {
+        quick_hash_file(path.to_string_lossy().to_string()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn quick_hash_changes_when_a_middle_insert_shifts_the_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = scratch_file(dir.path(), 4 * QUICK_HASH_WINDOW as usize);
+        let before = quick_hash(&path).await;
+        assert!(before.starts_with("quick:"));
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        let middle = bytes.len() / 2;
+        bytes.splice(middle..middle, *b"clausula nova");
+        std::fs::write(&path, bytes).unwrap();
+        assert_ne!(quick_hash(&path).await, before);
+    }
+
+    /// The documented limitation: an in-place edit between the two windows goes unseen.
+    #[tokio::test]
+    async fn quick_hash_misses_a_middle_only_edit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = scratch_file(dir.path(), 4 * QUICK_HASH_WINDOW as usize);
+        let before = quick_hash(&path).await;
+        let full_before = sha256_file(&path).unwrap();
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        let middle = bytes.len() / 2;
+        bytes[middle..middle + 13].copy_from_slice(b"clausula nova");
+        std::fs::write(&path, bytes).unwrap();
+        assert_eq!(quick_hash(&path).await, before);
+        assert_ne!(sha256_file(&path).unwrap(), full_before);
+    }
+
+    #[test]
+    fn extracted_text_round_trips() {
+        let conn = cache_db();
+        let short = "Excelentissimo Senhor Juiz";
+        let long = "Vistos, relatados e discutidos estes autos. ".repeat(200);
+        write_extracted_text(&conn, "curto", short).unwrap();
+        write_extracted_text(&conn, "longo", &long).unwrap();
+
+        assert_eq!(read_extracted_text(&conn, "curto").unwrap().as_deref(), Some(short));
+        assert_eq!(read_extracted_text(&conn, "longo").unwrap(), Some(long));
+        assert_eq!(read_extracted_text(&conn, "ausente").unwrap(), None);
+
+        write_extracted_text(&conn, "curto", "texto revisado").unwrap();
+        let revised = read_extracted_text(&conn, "curto").unwrap();
+        assert_eq!(revised.as_deref(), Some("texto revisado"));
+    }
+
+    /// `(namespace, file_hash, file_path, response, backend_url, cached_at)` of every row.
+    fn all_rows(conn: &Connection) -> Vec<(String, String, String, String, String, i64)> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT namespace, file_hash, file_path, api_response, compressed, backend_url,
+                        cached_at
+                 FROM api_cache ORDER BY namespace, file_hash",
+            )
+            .unwrap();
+        let rows = stmt
+            .query_map([], |row| {
+                let response = decode_response(row.get(3)?, row.get(4)?).unwrap();
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, response, row.get(5)?, row.get(6)?))
+            })
+            .unwrap();
+        rows.collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let mut source = cache_db();
+        let large = serde_json::json!({ "texto": "Autos conclusos. ".repeat(500) }).to_string();
+        let entries = [entry("a", r#"{"ok":1}"#), entry("b", &large)];
+        save_entries(&mut source, "default", &entries, false).unwrap();
+        save_entries(&mut source, "outro", &[entry("a", r#"{"ok":2}"#)], false).unwrap();
+        source.execute("UPDATE api_cache SET cached_at = 1000 WHERE file_hash = 'b'", []).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let mut out = BufWriter::new(std::fs::File::create(&path).unwrap());
+        assert_eq!(write_export(&source, &mut out).unwrap(), 3);
+        out.flush().unwrap();
+
+        let mut target = cache_db();
+        assert_eq!(import_rows(&mut target, &read_export(&path).unwrap()).unwrap(), 3);
+        assert_eq!(all_rows(&target), all_rows(&source));
+    }
+
+    #[test]
+    fn importing_a_known_file_upserts_its_rows() {
+        // Its second row predates namespaces and lands in "default".
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/cache-export.json");
+        let mut conn = cache_db();
+        insert_row(&conn, "h2", "http://velho", 50);
+
+        let rows = read_export(Path::new(path)).unwrap();
+        assert_eq!(import_rows(&mut conn, &rows).unwrap(), 2);
+        let expected = [
+            ("default", "h2", "/p/b.pdf", r#"{"n":2}"#, 200),
+            ("ocr", "h1", "/p/a.pdf", r#"{"n":1}"#, 100),
+        ]
+        .map(|(namespace, hash, path, response, cached_at)| {
+            let text = |s: &str| s.to_string();
+            (text(namespace), text(hash), text(path), text(response), text("http://b"), cached_at)
+        });
+        assert_eq!(all_rows(&conn), expected);
+    }
+
+    #[test]
+    fn a_malformed_import_file_is_rejected_whole() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        std::fs::write(&path, r#"[{"file_hash":"h1","file_path":"/p/a.pdf"}]"#).unwrap();
+        assert_eq!(read_export(&path).unwrap_err().code(), "INVALID_FILE");
+    }
+
+    #[test]
+    fn rows_for_deleted_files_are_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = cache_db();
+        let mut entries = Vec::new();
+        for name in ["mantido", "apagado"] {
+            let path = dir.path().join(format!("{}.pdf", name));
+            std::fs::write(&path, b"%PDF-1.4").unwrap();
+            let mut row = entry(name, "{}");
+            row.file_path = path.to_string_lossy().to_string();
+            entries.push(row);
+        }
+        save_entries(&mut conn, "default", &entries, false).unwrap();
+        std::fs::remove_file(dir.path().join("apagado.pdf")).unwrap();
+
+        assert_eq!(prune_orphans(&mut conn).unwrap(), 1);
+        assert_eq!(cached_hashes(&conn), ["mantido"]);
+        assert_eq!(prune_orphans(&mut conn).unwrap(), 0);
+    }
+
+    #[test]
+    fn mapped_and_streamed_digests_agree() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = scratch_file(dir.path(), 3 * MAX_HASH_BUFFER + 17);
+        let open = || std::fs::File::open(&path).unwrap();
+        let streamed = sha256_stream(open(), 8 * 1024).unwrap();
+        assert_eq!(sha256_mapped(&open()).unwrap(), streamed);
+        assert_eq!(sha256_stream(open(), MAX_HASH_BUFFER).unwrap(), streamed);
+        assert_eq!(sha256_file(&path).unwrap(), streamed);
+    }
+
+    /// Prints mmap vs streaming times per file size; the basis for
+    /// `MMAP_THRESHOLD`. Run with
+    /// `cargo test --release --lib mmap_crossover -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn mmap_crossover() {
+        let dir = tempfile::tempdir().unwrap();
+        for kb in [16, 64, 256, 1024, 4096, 16384, 65536, 262144] {
+            let len = kb * 1024;
+            let path = scratch_file(dir.path(), len);
+            let open = || std::fs::File::open(&path).unwrap();
+            let streamed = best_ms(20, || {
+                sha256_stream(open(), hash_buffer_size(len as u64)).unwrap();
+            });
+            let mapped = best_ms(20, || {
+                sha256_mapped(&open()).unwrap();
+            });
+            println!("{:>7} KB  stream {:>8.3} ms  mmap {:>8.3} ms", kb, streamed, mapped);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    /// Prints streaming times with the old fixed 8 KB buffer against
+    /// `hash_buffer_size`; the basis for its doc table. Run with
+    /// `cargo test --release --lib buffer_sizes -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn buffer_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        for kb in [16, 256, 1024, 65536, 262144] {
+            let len = kb * 1024;
+            let path = scratch_file(dir.path(), len);
+            let open = || std::fs::File::open(&path).unwrap();
+            let fixed = best_ms(20, || {
+                sha256_stream(open(), 8 * 1024).unwrap();
+            });
+            let adaptive = best_ms(20, || {
+                sha256_stream(open(), hash_buffer_size(len as u64)).unwrap();
+            });
+            println!("{:>7} KB  8 KB {:>8.3} ms  adaptive {:>8.3} ms", kb, fixed, adaptive);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    /// Prints the invalidation and expiry lookups on a 50,000-row cache with and
+    /// without the v6 indexes; the basis for the v6 migration comment. Run with
+    /// `cargo test --release --lib cache_index_timings -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn cache_index_timings() {
+        const ROWS: i64 = 50_000;
+        const BACKENDS: i64 = 20;
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&mut conn).unwrap();
+        let response = "x".repeat(1024);
+        let tx = conn.transaction().unwrap();
+        for i in 0..ROWS {
+            tx.execute(
+                "INSERT INTO api_cache (file_hash, file_path, api_response, backend_url, cached_at)
+                 VALUES (?, ?, ?, ?, ?)",
+                params![
+                    format!("{:064x}", i),
+                    format!("/processos/{}.pdf", i),
+                    response,
+                    format!("http://backend-{}:8000", i % BACKENDS),
+                    1_700_000_000 + (i * 7919) % ROWS * 60,
+                ],
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+        conn.execute_batch("ANALYZE").unwrap();
+
+        let by_backend =
+            "SELECT COUNT(*) FROM api_cache WHERE namespace = 'default' AND backend_url = ?1";
+        let expired = format!("SELECT COUNT(*) FROM ({})", EXPIRED_SELECTION);
+        // The oldest 5% of rows.
+        let cutoff = 1_700_000_000 + ROWS / 20 * 60;
+        let measure = |conn: &Connection, label: &str| {
+            let plan: String = conn
+                .query_row(&format!("EXPLAIN QUERY PLAN {}", by_backend), ["x"], |row| row.get(3))
+                .unwrap();
+            let backend_ms = best_ms(20, || {
+                let _: i64 = conn
+                    .query_row(by_backend, ["http://backend-7:8000"], |row| row.get(0))
+                    .unwrap();
+            });
+            let explain = format!("EXPLAIN QUERY PLAN {}", expired);
+            let expiry_plan: String = conn
+                .query_row(&explain, params!["default", cutoff], |row| row.get(3))
+                .unwrap();
+            let expired_ms = best_ms(20, || {
+                let _: i64 = conn
+                    .query_row(&expired, params!["default", cutoff], |row| row.get(0))
+                    .unwrap();
+            });
+            println!(
+                "{}: backend {:.3} ms ({}), expiry {:.3} ms ({})",
+                label, backend_ms, plan, expired_ms, expiry_plan
+            );
+        };
+
+        measure(&conn, "indexed");
+        conn.execute_batch(
+            "DROP INDEX idx_api_cache_backend_url; DROP INDEX idx_api_cache_cached_at; ANALYZE;",
+        )
+        .unwrap();
+        measure(&conn, "unindexed");
+    }
+
+    #[tokio::test]
+    async fn folder_hash_changes_when_a_pdf_is_added_or_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("inicial.pdf"), b"%PDF-1.4 inicial").unwrap();
+        let folder = dir.path().to_string_lossy().to_string();
+        let before = hash_folder(folder.clone()).await.unwrap();
+        assert_eq!(hash_folder(folder.clone()).await.unwrap(), before);
+
+        let added = dir.path().join("sentenca.pdf");
+        std::fs::write(&added, b"%PDF-1.4 sentenca").unwrap();
+        let with_added = hash_folder(folder.clone()).await.unwrap();
+        assert_ne!(with_added, before);
+
+        std::fs::remove_file(&added).unwrap();
+        assert_eq!(hash_folder(folder.clone()).await.unwrap(), before);
+        std::fs::remove_file(dir.path().join("inicial.pdf")).unwrap();
+        assert_ne!(hash_folder(folder).await.unwrap(), before);
+    }
+
+    #[test]
+    fn shrinking_evicts_the_least_recent_rows_until_under_the_cap() {
+        let mut conn = cache_db();
+        let response = "x".repeat(10_000);
+        for (i, hash) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            insert_row(&conn, hash, "http://backend", 1_000 + i as i64);
+        }
+        conn.execute("UPDATE api_cache SET api_response = ?1", [&response]).unwrap();
+
+        let cap = 25_000;
+        assert_eq!(shrink_to(&mut conn, cap, true).unwrap(), 3);
+        assert_eq!(cached_hashes(&conn), ["d", "e"]);
+        let stored: i64 = conn
+            .query_row(
+                "SELECT SUM(LENGTH(CAST(api_response AS BLOB)) + ?1) FROM api_cache",
+                params![ROW_OVERHEAD_BYTES],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(stored <= cap as i64, "{} bytes", stored);
+        assert_eq!(shrink_to(&mut conn, cap, true).unwrap(), 0);
+    }
+
+    #[test]
+    fn clearing_one_namespace_leaves_the_other_untouched() {
+        let mut conn = cache_db();
+        save_entries(&mut conn, "caso-a", &[entry("a1", "{}"), entry("a2", "{}")], false).unwrap();
+        save_entries(&mut conn, "caso-b", &[entry("b1", "{}")], false).unwrap();
+
+        assert_eq!(clear_namespace(&conn, "caso-a").unwrap(), 2);
+        assert_eq!(namespace_stats(&conn, "caso-a").unwrap().entry_count, 0);
+        assert_eq!(namespace_stats(&conn, "caso-b").unwrap().entry_count, 1);
+        assert_eq!(cached_result(&conn, "caso-b", "b1", None).unwrap().as_deref(), Some("{}"));
+        assert_eq!(cached_result(&conn, "caso-a", "a1", None).unwrap(), None);
+    }
+
+    #[test]
+    fn a_save_is_audited_only_while_auditing_is_on() {
+        let mut conn = cache_db();
+        save_entries(&mut conn, "default", &[entry("abc", "{}")], true).unwrap();
+        save_entries(&mut conn, "default", &[entry("privado", "{}")], false).unwrap();
+
+        let audit = recent_audit(&conn, 10).unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].file_hash, "abc");
+        assert_eq!(audit[0].namespace, "default");
+        assert_eq!(audit[0].backend_url, "http://backend");
+        assert_eq!(audit[0].action, "save");
+        assert!(audit[0].timestamp > 0);
+    }
+
+    #[test]
+    fn only_files_without_a_cached_row_are_returned() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut hashed = Vec::new();
+        for name in ["cacheado.pdf", "novo.pdf", "outro_caso.pdf"] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, format!("%PDF-1.4 {}", name)).unwrap();
+            let hash = hash_path(&path, HashAlgorithm::Sha256).unwrap();
+            hashed.push((path.to_string_lossy().to_string(), Some(hash)));
+        }
+        let missing = dir.path().join("removido.pdf").to_string_lossy().to_string();
+        hashed.push((missing.clone(), None));
+        let mut conn = cache_db();
+        let hash_of = |i: usize| hashed[i].1.as_deref().unwrap();
+        save_entries(&mut conn, "default", &[entry(hash_of(0), "{}")], false).unwrap();
+        save_entries(&mut conn, "outro", &[entry(hash_of(2), "{}")], false).unwrap();
+
+        let expected = [hashed[1].0.clone(), hashed[2].0.clone(), missing];
+        assert_eq!(uncached_paths(&conn, "default", hashed).unwrap(), expected);
+    }
+
+    #[test]
+    fn closing_waits_for_a_pending_write_and_checkpoints_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let mut conn = open_connection(&path).unwrap();
+        run_migrations(&mut conn).unwrap();
+        let db = CacheDb { conn: Mutex::new(Some(conn)) };
+
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut guard = db.conn.lock().unwrap();
+                locked_tx.send(()).unwrap();
+                // Still mid-batch when the exit handler runs.
+                std::thread::sleep(Duration::from_millis(100));
+                save_entries(guard.as_mut().unwrap(), "default", &[entry("abc", "{}")], false)
+                    .unwrap();
+            });
+            locked_rx.recv().unwrap();
+            db.close();
+        });
+        assert!(db.conn.lock().unwrap().is_none());
+
+        // Only the main file is copied: the row must have left the WAL.
+        let copy = dir.path().join("copia.db");
+        std::fs::copy(&path, &copy).unwrap();
+        let reopened = Connection::open(&copy).unwrap();
+        assert_eq!(cached_hashes(&reopened), ["abc"]);
+    }
+
+    #[tokio::test]
+    async fn editing_a_file_misses_the_folder_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf = dir.path().join("peticao.pdf");
+        std::fs::write(&pdf, b"%PDF-1.4 original").unwrap();
+        let folder = dir.path().to_string_lossy().to_string();
+        let mut conn = cache_db();
+        let hash = hash_folder(folder.clone()).await.unwrap();
+        store_folder_result(&mut conn, &hash, &folder, r#"{"partes":2}"#).unwrap();
+        let current = hash_folder(folder.clone()).await.unwrap();
+        assert_eq!(folder_result(&conn, &current).unwrap().as_deref(), Some(r#"{"partes":2}"#));
+
+        std::fs::write(&pdf, b"%PDF-1.4 alterado").unwrap();
+        let changed = hash_folder(folder.clone()).await.unwrap();
+        assert_eq!(folder_result(&conn, &changed).unwrap(), None);
+        // Storing the new result replaces the stale row for the folder.
+        store_folder_result(&mut conn, &changed, &folder, r#"{"partes":3}"#).unwrap();
+        assert_eq!(folder_result(&conn, &hash).unwrap(), None);
+    }
+
+    #[test]
+    fn listing_by_backend_returns_only_its_newest_rows() {
+        let conn = cache_db();
+        insert_row(&conn, "a1", "http://backend-a", 100);
+        insert_row(&conn, "a2", "http://backend-a", 300);
+        insert_row(&conn, "a3", "http://backend-a", 200);
+        insert_row(&conn, "b1", "http://backend-b", 400);
+
+        let entries = backend_entries(&conn, "default", "http://backend-a", 2, Some("a3")).unwrap();
+        let hashes: Vec<_> = entries.iter().map(|entry| entry.file_hash.as_str()).collect();
+        assert_eq!(hashes, ["a2", "a3"]);
+        assert_eq!(entries[0].api_response, None);
+        assert_eq!(entries[1].api_response.as_deref(), Some("{}"));
+        assert!(backend_entries(&conn, "outro", "http://backend-a", 10, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn verification_flags_empty_and_malformed_rows_without_deleting() {
+        let mut conn = cache_db();
+        save_entries(&mut conn, "default", &[entry("bom", r#"{"ok":true}"#)], false).unwrap();
+        insert_row(&conn, "vazio", "http://backend", 1_000);
+        insert_row(&conn, "quebrado", "http://backend", 1_000);
+        for (hash, response) in [("vazio", "  "), ("quebrado", r#"{"a":"#)] {
+            let sql = "UPDATE api_cache SET api_response = ?1 WHERE file_hash = ?2";
+            conn.execute(sql, params![response, hash]).unwrap();
+        }
+
+        let report = check_integrity(&conn).unwrap();
+        assert!(report.integrity_ok, "{:?}", report.integrity_messages);
+        assert_eq!(report.row_count, 3);
+        assert_eq!(report.empty_responses, ["vazio"]);
+        assert_eq!(report.malformed_responses, ["quebrado"]);
+        assert_eq!(row_count(&conn), 3);
+    }
+
+    #[test]
+    fn recent_files_come_back_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let raw = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        for name in ["peticao.pdf", "sentenca.pdf", "removido.pdf"] {
+            std::fs::write(dir.path().join(name), b"%PDF-1.4").unwrap();
+        }
+        let mut conn = cache_db();
+        record_access(&mut conn, &raw("removido.pdf")).unwrap();
+        record_access(&mut conn, &raw("peticao.pdf")).unwrap();
+        record_access(&mut conn, &raw("sentenca.pdf")).unwrap();
+        std::fs::remove_file(dir.path().join("removido.pdf")).unwrap();
+
+        let recent = existing_recent(recent_rows(&conn).unwrap(), 10);
+        let names: Vec<_> = recent.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, ["sentenca.pdf", "peticao.pdf"]);
+        assert_eq!(recent[0].path, raw("sentenca.pdf"));
+
+        // Opening an older file again moves it back to the top.
+        record_access(&mut conn, &raw("peticao.pdf")).unwrap();
+        let recent = existing_recent(recent_rows(&conn).unwrap(), 1);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].name, "peticao.pdf");
+    }
+
+    #[test]
+    fn commands_before_init_get_a_migrated_db() {
+        let dir = tempfile::tempdir().unwrap();
+        let fresh = dir.path().join("novo.db");
+        let db = CacheDb::default();
+        let lookup = |conn: &mut Connection| cached_result(conn, "default", "abc", None);
+        assert_eq!(db.with(|| fresh.clone(), lookup).unwrap(), None);
+
+        // A file left by a build from before namespaces is upgraded on open too.
+        let old = dir.path().join("antigo.db");
+        Connection::open(&old).unwrap().execute_batch(MIGRATIONS[0]).unwrap();
+        let db = CacheDb::default();
+        db.with(|| old.clone(), |conn| save_entries(conn, "caso", &[entry("abc", "{}")], true))
+            .unwrap();
+        let found = db.with(|| old.clone(), |conn| cached_result(conn, "caso", "abc", None));
+        assert_eq!(found.unwrap().as_deref(), Some("{}"));
+    }
 }
@@ -0,0 +1,377 @@
+use crate::commands::{cache, filesystem, pdf};
+use crate::models::{
+    AppError, ExtractionEstimate, ExtractionProgress, ExtractionStarted, ExtractionStatus,
+    ExtractionStatusCounts, ExtractionSummary, HashAlgorithm, PageCountSummary, RetryReport,
+};
+use crate::settings;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+
+/// Characters of text carried by each `document-extracted` event.
+const PREVIEW_CHARS: usize = 280;
+
+/// Pause between two attempts at the same file in `retry_failed_extractions`.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Assumed throughput before any batch has been timed.
+const DEFAULT_PAGES_PER_SECOND: f64 = 5.0;
+
+/// Batches of history after which an estimate is reported as fully confident.
+const CONFIDENT_SAMPLES: u32 = 5;
+
+/// Last known status of every extracted file and how many times it was tried.
+/// Managed as Tauri state.
+/// Also counts progress through the current `extract_folder` batch.
+#[derive(Default)]
+pub struct ExtractionRegistry {
+    files: Mutex<HashMap<String, (ExtractionStatus, u32)>>,
+    batch_done: AtomicUsize,
+    batch_total: AtomicUsize,
+}
+
+impl ExtractionRegistry {
+    /// Marks `path` as being extracted without counting an attempt.
+    fn start(&self, path: &str) {
+        let mut files = self.files.lock().unwrap();
+        files.entry(path.to_string()).or_insert((ExtractionStatus::Pending, 0)).0 =
+            ExtractionStatus::InProgress;
+    }
+
+    pub(crate) fn status_of(&self, path: &str) -> Option<ExtractionStatus> {
+        self.files.lock().unwrap().get(path).map(|(status, _)| status.clone())
+    }
+
+    /// Status reported for `path` outside a batch: this session's status when
+    /// there is one, else `Completed` if the file's content has stored text
+    /// (`extracted` comes from `cache::extracted_hashes`), else `Pending`.
+    pub(crate) fn reported_status(
+        &self,
+        path: &str,
+        extracted: &HashSet<String>,
+    ) -> ExtractionStatus {
+        self.status_of(path).unwrap_or_else(|| {
+            match cache::hash_path(Path::new(path), HashAlgorithm::Sha256) {
+                Ok(hash) if extracted.contains(&hash) => ExtractionStatus::Completed,
+                _ => ExtractionStatus::Pending,
+            }
+        })
+    }
+
+    fn record(&self, path: &str, status: &ExtractionStatus) {
+        let mut files = self.files.lock().unwrap();
+        let entry = files.entry(path.to_string()).or_insert((ExtractionStatus::Pending, 0));
+        entry.0 = status.clone();
+        entry.1 += 1;
+    }
+
+    /// Failed files under `folder` with their last status and attempt count.
+    fn failed_in(&self, folder: &Path) -> Vec<(String, ExtractionStatus, u32)> {
+        self.files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(path, (status, _))| {
+                matches!(status, ExtractionStatus::Failed(_)) && Path::new(path).starts_with(folder)
+            })
+            .map(|(path, (status, attempts))| (path.clone(), status.clone(), *attempts))
+            .collect()
+    }
+}
+
+#[tauri::command]
+pub async fn extract_folder(
+    app: tauri::AppHandle,
+    folder_path: String,
+    max_concurrency: usize,
+    timeout_seconds: Option<u64>,
+) -> Result<ExtractionSummary, AppError> {
+    let path = Path::new(&folder_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+
+    let pdfs = filesystem::find_pdfs(path)?;
+    let semaphore = Arc::new(Semaphore::new(max_concurrency.max(1)));
+    let timeout = timeout_seconds.map(Duration::from_secs);
+    let mut summary = ExtractionSummary {
+        total: pdfs.len(),
+        succeeded: 0,
+        failed: 0,
+        needs_ocr: 0,
+    };
+
+    {
+        let registry = app.state::<ExtractionRegistry>();
+        registry.batch_done.store(0, Ordering::SeqCst);
+        registry.batch_total.store(summary.total, Ordering::SeqCst);
+    }
+    let _ = app.emit("extraction-started", ExtractionStarted { total: summary.total });
+
+    let started = Instant::now();
+    let mut tasks = JoinSet::new();
+    for pdf in pdfs {
+        let app = app.clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let permit = semaphore.acquire_owned().await.expect("extraction semaphore closed");
+            app.state::<ExtractionRegistry>().start(&pdf.path);
+            let (status, preview, pages) =
+                extract_one(app.clone(), PathBuf::from(&pdf.path), timeout, Some(permit)).await;
+            let registry = app.state::<ExtractionRegistry>();
+            registry.record(&pdf.path, &status);
+            registry.batch_done.fetch_add(1, Ordering::SeqCst);
+            let _ = app.emit("document-extracted", ExtractionProgress {
+                path: pdf.path,
+                status: status.clone(),
+                preview,
+            });
+            (status, pages)
+        });
+    }
+
+    let mut extracted_pages = 0;
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((ExtractionStatus::Completed, pages)) => {
+                summary.succeeded += 1;
+                extracted_pages += pages;
+            }
+            Ok((ExtractionStatus::NeedsOcr, _)) => summary.needs_ocr += 1,
+            _ => summary.failed += 1,
+        }
+    }
+
+    // Stats only feed estimates, so failing to save them doesn't fail the batch.
+    let _ = cache::record_extraction_rate(&app, extracted_pages, started.elapsed());
+    notify_batch_complete(&app, &summary);
+    Ok(summary)
+}
+
+/// `(done, total)` for the most recent `extract_folder` batch, for frontends
+/// that poll instead of listening to `document-extracted`.
+#[tauri::command]
+pub async fn extraction_progress(
+    registry: tauri::State<'_, ExtractionRegistry>,
+) -> Result<(usize, usize), AppError> {
+    Ok((
+        registry.batch_done.load(Ordering::SeqCst),
+        registry.batch_total.load(Ordering::SeqCst),
+    ))
+}
+
+/// Estimates how long `extract_folder` would take on `folder_path` from its
+/// total page count and the rolling throughput of earlier batches. With no
+/// history a default rate is used and `confidence` is 0. Files whose text is
+/// already cached are counted too, so the estimate errs on the long side.
+#[tauri::command]
+pub async fn estimate_extraction_time(
+    app: tauri::AppHandle,
+    folder_path: String,
+) -> Result<ExtractionEstimate, AppError> {
+    let pages = pdf::total_page_count(folder_path).await?;
+    Ok(estimate(&pages, cache::extraction_rate(&app)?))
+}
+
+/// Applies `rate` (pages per second and the batches it averages) to `pages`.
+fn estimate(pages: &PageCountSummary, rate: Option<(f64, u32)>) -> ExtractionEstimate {
+    let (pages_per_second, samples) = rate.unwrap_or((DEFAULT_PAGES_PER_SECOND, 0));
+    ExtractionEstimate {
+        total_pages: pages.total_pages,
+        file_count: pages.file_count,
+        pages_per_second,
+        estimated_seconds: pages.total_pages as f64 / pages_per_second,
+        confidence: samples.min(CONFIDENT_SAMPLES) as f64 / CONFIDENT_SAMPLES as f64,
+        samples,
+    }
+}
+
+/// Counts the PDFs under `folder_path` by extraction status. Statuses seen this
+/// session win; otherwise a file whose content has stored text is `Completed`,
+/// and anything else is `Pending`.
+#[tauri::command]
+pub async fn extraction_status_summary(
+    app: tauri::AppHandle,
+    folder_path: String,
+) -> Result<ExtractionStatusCounts, AppError> {
+    let path = Path::new(&folder_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+
+    let registry = app.state::<ExtractionRegistry>();
+    let extracted = cache::extracted_hashes(&app)?;
+    let statuses: Vec<(String, ExtractionStatus)> = filesystem::find_pdfs(path)?
+        .into_par_iter()
+        .map(|pdf| {
+            let status = registry.reported_status(&pdf.path, &extracted);
+            (pdf.path, status)
+        })
+        .collect();
+
+    let mut counts = ExtractionStatusCounts::default();
+    for (path, status) in statuses {
+        match status {
+            ExtractionStatus::Pending => counts.pending += 1,
+            ExtractionStatus::InProgress => counts.in_progress += 1,
+            ExtractionStatus::Completed => counts.completed += 1,
+            ExtractionStatus::NeedsOcr => counts.needs_ocr += 1,
+            ExtractionStatus::Failed(_) => {
+                counts.failed += 1;
+                counts.failed_paths.push(path);
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// Re-runs extraction for the files under `folder_path` whose last status is
+/// `Failed`, retrying each until it succeeds or has been tried `max_attempts`
+/// times in total, counting earlier runs. Files past the cap are reported as
+/// still failed without being touched again.
+#[tauri::command]
+pub async fn retry_failed_extractions(
+    app: tauri::AppHandle,
+    folder_path: String,
+    max_attempts: u32,
+) -> Result<RetryReport, AppError> {
+    let folder = Path::new(&folder_path);
+    if !folder.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+
+    let registry = app.state::<ExtractionRegistry>();
+    let mut report = RetryReport {
+        recovered: Vec::new(),
+        still_failed: Vec::new(),
+    };
+
+    let mut failed = registry.failed_in(folder);
+    failed.sort_by(|a, b| a.0.cmp(&b.0));
+    for (path, mut status, mut attempts) in failed {
+        let mut preview = None;
+        while attempts < max_attempts {
+            if attempts > 0 {
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+            (status, preview, _) = extract_one(app.clone(), PathBuf::from(&path), None, None).await;
+            registry.record(&path, &status);
+            attempts += 1;
+            if !matches!(status, ExtractionStatus::Failed(_)) {
+                break;
+            }
+        }
+
+        let _ = app.emit("document-extracted", ExtractionProgress {
+            path: path.clone(),
+            status: status.clone(),
+            preview,
+        });
+        match status {
+            ExtractionStatus::Failed(error) => report.still_failed.push((path, error)),
+            _ => report.recovered.push(path),
+        }
+    }
+
+    Ok(report)
+}
+
+fn notify_batch_complete(app: &tauri::AppHandle, summary: &ExtractionSummary) {
+    if !settings::get_bool(app, settings::NOTIFY_ON_BATCH_COMPLETE, true) {
+        return;
+    }
+
+    let mut body = format!("{} documentos processados, {} com falha", summary.total, summary.failed);
+    if summary.needs_ocr > 0 {
+        body.push_str(&format!(", {} precisam de OCR", summary.needs_ocr));
+    }
+    let _ = app
+        .notification()
+        .builder()
+        .title("Extracao concluida")
+        .body(body)
+        .show();
+}
+
+/// Runs the blocking extractor off the async runtime and maps the outcome to a
+/// status, a short preview of the text and the number of pages actually
+/// extracted. Files whose text is already in the cache are reported
+/// `Completed` without re-reading them (0 pages); fresh results are stored
+/// for the next run. Image-only PDFs are reported as `NeedsOcr` instead
+/// of an empty success. A file still running after `timeout` is abandoned as
+/// `Failed("timeout")`; its blocking thread finishes on its own and is ignored.
+/// `permit` lives inside that thread, so an abandoned file keeps its slot
+/// until the work really stops and `max_concurrency` holds for threads too.
+async fn extract_one(
+    app: tauri::AppHandle,
+    path: PathBuf,
+    timeout: Option<Duration>,
+    permit: Option<OwnedSemaphorePermit>,
+) -> (ExtractionStatus, Option<String>, usize) {
+    let task = tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        // Cache trouble only costs a re-extraction, so it never fails the file.
+        let file_hash = cache::hash_path(&path, HashAlgorithm::Sha256)?;
+        if let Ok(Some(text)) = cache::load_extracted_text(&app, &file_hash) {
+            return Ok((ExtractionStatus::Completed, Some(preview_of(&text)), 0));
+        }
+
+        let doc = pdf::load_document(&path)?;
+        if pdf::is_scanned(&doc) {
+            return Ok((ExtractionStatus::NeedsOcr, None, 0));
+        }
+        let text = pdf::document_text(&doc, &path)?;
+        let _ = cache::store_extracted_text(&app, &file_hash, &text);
+        let pages = doc.get_pages().len();
+        Ok::<_, AppError>((ExtractionStatus::Completed, Some(preview_of(&text)), pages))
+    });
+
+    let extracted = match timeout {
+        Some(limit) => match tokio::time::timeout(limit, task).await {
+            Ok(joined) => joined,
+            Err(_) => return (ExtractionStatus::Failed("timeout".to_string()), None, 0),
+        },
+        None => task.await,
+    };
+
+    match extracted {
+        Ok(Ok(outcome)) => outcome,
+        Ok(Err(e)) => (ExtractionStatus::Failed(e.to_string()), None, 0),
+        Err(e) => (ExtractionStatus::Failed(e.to_string()), None, 0),
+    }
+}
+
+fn preview_of(text: &str) -> String {
+    text.trim_start().chars().take(PREVIEW_CHARS).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(total_pages: usize) -> PageCountSummary {
+        PageCountSummary { total_pages, file_count: 3, corrupt: Vec::new() }
+    }
+
+    #[test]
+    fn the_estimate_scales_with_page_count() {
+        let short = estimate(&pages(100), Some((20.0, 2)));
+        let long = estimate(&pages(400), Some((20.0, 2)));
+        assert_eq!(short.estimated_seconds, 5.0);
+        assert_eq!(long.estimated_seconds, 4.0 * short.estimated_seconds);
+        assert_eq!(short.confidence, 0.4);
+
+        let unseen = estimate(&pages(100), None);
+        assert_eq!(unseen.pages_per_second, DEFAULT_PAGES_PER_SECOND);
+        assert_eq!(unseen.estimated_seconds, 100.0 / DEFAULT_PAGES_PER_SECOND);
+        assert_eq!(unseen.confidence, 0.0);
+        assert_eq!(estimate(&pages(100), Some((20.0, 50))).confidence, 1.0);
+    }
+}
@@ -1,91 +1,1976 @@
-use crate::models::{AppError, PdfFile, ProcessFolder, ExtractionStatus};
+use crate::commands::{cache, extraction::ExtractionRegistry, pdf};
+use crate::models::{
+    AppError, ExtractionStatus, FolderScanOptions, FolderSortMode, FolderSummary, HashAlgorithm,
+    MultiRootListing, PathContext, PdfFile, PdfListing, PdfPage, PdfScanOptions, ProcessFolder,
+    ProcessFolderListing, ScanComplete, ScanProgress,
+};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+use tauri::{Emitter, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+/// How many entries a walk visits between checks of the cancellation set.
+const CANCEL_CHECK_INTERVAL: usize = 64;
+
+/// Minimum interval between `scan-progress` events.
+const PROGRESS_THROTTLE: Duration = Duration::from_millis(100);
+
+/// Running scans by ID, each with whether the frontend asked to cancel it.
+/// Managed as Tauri state.
+#[derive(Default)]
+pub struct ScanRegistry {
+    running: Mutex<HashMap<String, bool>>,
+}
+
+impl ScanRegistry {
+    /// Registers a scan as running. Reusing the ID of a finished scan starts it
+    /// uncancelled; an ID that is still running is rejected, since the two
+    /// scans would share one cancel flag and the first to finish would drop it.
+    fn begin(&self, scan_id: &str) -> Result<(), AppError> {
+        let mut running = self.running.lock().unwrap();
+        if running.contains_key(scan_id) {
+            return Err(AppError::InvalidArgument(format!("scan_id {} ja em uso", scan_id)));
+        }
+        running.insert(scan_id.to_string(), false);
+        Ok(())
+    }
+
+    fn is_cancelled(&self, scan_id: &str) -> bool {
+        self.running.lock().unwrap().get(scan_id).copied().unwrap_or(false)
+    }
+
+    /// Flags a running scan for cancellation; unknown or finished IDs are ignored.
+    fn cancel(&self, scan_id: &str) {
+        if let Some(cancelled) = self.running.lock().unwrap().get_mut(scan_id) {
+            *cancelled = true;
+        }
+    }
+
+    fn finish(&self, scan_id: &str) {
+        self.running.lock().unwrap().remove(scan_id);
+    }
+}
+
+/// Options shared by the PDF listing commands and threaded through the recursive walk.
+struct ScanOptions {
+    /// Lowercased extensions without the leading dot.
+    extensions: Vec<String>,
+    /// Descend into symlinked directories and list symlinked files.
+    follow_symlinks: bool,
+    /// Deepest directory level to descend into; the scanned folder is depth 0.
+    max_depth: Option<usize>,
+    /// Parse each PDF's page tree to fill `page_count`; slows the scan.
+    with_page_count: bool,
+    /// Lowercased substring the filename must contain.
+    name_query: Option<String>,
+    /// Skip dot-entries and OS junk folders such as `__MACOSX`.
+    skip_hidden: bool,
+    /// Prefix stripped from each listed `PdfFile.path`.
+    relative_to: Option<PathBuf>,
+    /// Compare digit runs in names numerically.
+    natural_sort: bool,
+    /// Decide PDF-ness from the file header instead of the extension.
+    verify_magic: bool,
+}
+
+impl ScanOptions {
+    fn new(request: PdfScanOptions) -> Self {
+        Self {
+            extensions: normalize_extensions(request.extensions),
+            follow_symlinks: request.follow_symlinks,
+            max_depth: request.max_depth,
+            with_page_count: request.with_page_count,
+            name_query: None,
+            skip_hidden: request.skip_hidden,
+            relative_to: request.relative_to.map(PathBuf::from),
+            natural_sort: request.natural_sort,
+            verify_magic: request.verify_magic,
+        }
+    }
+
+    /// Counting options for process folders, so `pdf_count` matches what a
+    /// listing with the same extensions would return.
+    fn for_folders(options: &FolderScanOptions) -> Self {
+        Self::new(PdfScanOptions {
+            extensions: options.extensions.clone(),
+            skip_hidden: options.skip_hidden,
+            ..PdfScanOptions::default()
+        })
+    }
+}
+
+/// Periodically consults the registry while a walk is in progress.
+struct CancelCheck<'a> {
+    registry: Option<&'a ScanRegistry>,
+    scan_id: Option<&'a str>,
+    visited: usize,
+}
+
+impl<'a> CancelCheck<'a> {
+    fn new(registry: &'a ScanRegistry, scan_id: Option<&'a str>) -> Self {
+        Self { registry: Some(registry), scan_id, visited: 0 }
+    }
+
+    /// For walks that can't be cancelled, e.g. inside single-shot commands.
+    fn never() -> Self {
+        Self { registry: None, scan_id: None, visited: 0 }
+    }
+
+    fn tick(&mut self) -> Result<(), AppError> {
+        self.visited += 1;
+        if !self.visited.is_multiple_of(CANCEL_CHECK_INTERVAL) {
+            return Ok(());
+        }
+        self.check()
+    }
+
+    /// Consults the registry now, regardless of how many entries were visited.
+    fn check(&self) -> Result<(), AppError> {
+        match (self.registry, self.scan_id) {
+            (Some(registry), Some(id)) if registry.is_cancelled(id) => {
+                Err(AppError::Cancelled(id.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
 
 #[tauri::command]
-pub async fn list_process_folders(root_path: String) -> Result<Vec<ProcessFolder>, AppError> {
+pub async fn cancel_scan(
+    registry: tauri::State<'_, ScanRegistry>,
+    scan_id: String,
+) -> Result<(), AppError> {
+    registry.cancel(&scan_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_process_folders(
+    app: tauri::AppHandle,
+    registry: tauri::State<'_, ScanRegistry>,
+    root_path: String,
+    scan_id: Option<String>,
+    options: Option<FolderScanOptions>,
+) -> Result<ProcessFolderListing, AppError> {
     let path = Path::new(&root_path);
     if !path.is_dir() {
         return Err(AppError::InvalidDirectory(root_path));
     }
 
-    let mut folders = Vec::new();
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let entry_path = entry.path();
-        if entry_path.is_dir() {
-            let pdf_count = count_pdfs(&entry_path);
-            let metadata = entry.metadata()?;
-
-            folders.push(ProcessFolder {
-                path: entry_path.to_string_lossy().to_string(),
-                name: entry.file_name().to_string_lossy().to_string(),
-                pdf_count,
-                total_size_bytes: calculate_dir_size(&entry_path),
-                last_modified: format_time(metadata.modified()?),
-            });
+    let options = options.unwrap_or_default();
+    if let Some(id) = &scan_id {
+        registry.begin(id)?;
+    }
+    let progress = ProgressEmitter::new(&app);
+    let result = scan_process_folders(path, &registry, scan_id.as_deref(), &options, &progress);
+    if let Some(id) = &scan_id {
+        registry.finish(id);
+    }
+
+    let mut listing = result?;
+    let _ = app.emit("scan-complete", ScanComplete { total: listing.folders.len() });
+    // Without a sort mode this is the historical newest-first order.
+    let descending = options.descending.unwrap_or(options.sort_by.is_none());
+    sort_folders(&mut listing.folders, options.sort_by.unwrap_or_default(), descending);
+    Ok(listing)
+}
+
+/// Lists the process folders of several roots at once, scanning the roots in
+/// parallel, newest first across all of them. Each folder's `root` says where
+/// it came from. A root that isn't a readable directory is reported in
+/// `skipped` instead of failing the whole call. Progress counts folders across
+/// all roots and `scan-complete` fires once, after the last root; `scan_id`
+/// cancels the whole call like it does for `list_process_folders`.
+#[tauri::command]
+pub async fn list_process_folders_multi(
+    app: tauri::AppHandle,
+    registry: tauri::State<'_, ScanRegistry>,
+    root_paths: Vec<String>,
+    scan_id: Option<String>,
+) -> Result<MultiRootListing, AppError> {
+    let options = FolderScanOptions::default();
+    if let Some(id) = &scan_id {
+        registry.begin(id)?;
+    }
+    let progress = ProgressEmitter::new(&app);
+    let result = scan_roots(root_paths, &registry, scan_id.as_deref(), &options, &progress);
+    if let Some(id) = &scan_id {
+        registry.finish(id);
+    }
+
+    let listing = result?;
+    let _ = app.emit("scan-complete", ScanComplete { total: listing.folders.len() });
+    Ok(listing)
+}
+
+fn scan_roots(
+    root_paths: Vec<String>,
+    registry: &ScanRegistry,
+    scan_id: Option<&str>,
+    options: &FolderScanOptions,
+    progress: &ProgressEmitter,
+) -> Result<MultiRootListing, AppError> {
+    let results: Vec<(String, Result<ProcessFolderListing, AppError>)> = root_paths
+        .into_par_iter()
+        .map(|root_path| {
+            let path = Path::new(&root_path);
+            let result = if path.is_dir() {
+                scan_process_folders(path, registry, scan_id, options, progress)
+            } else {
+                Err(AppError::InvalidDirectory(root_path.clone()))
+            };
+            (root_path, result)
+        })
+        .collect();
+    merge_root_listings(results)
+}
+
+/// Combines per-root results newest first. A cancelled root cancels the whole
+/// listing; any other failure only skips that root.
+fn merge_root_listings(
+    results: Vec<(String, Result<ProcessFolderListing, AppError>)>,
+) -> Result<MultiRootListing, AppError> {
+    let mut listing = MultiRootListing { folders: Vec::new(), skipped: Vec::new() };
+    for (root_path, result) in results {
+        match result {
+            Ok(root_listing) => listing.folders.extend(root_listing.folders),
+            Err(e @ AppError::Cancelled(_)) => return Err(e),
+            Err(e) => listing.skipped.push((root_path, e.to_string())),
         }
     }
+    sort_folders(&mut listing.folders, FolderSortMode::LastModified, true);
+    Ok(listing)
+}
+
+#[tauri::command]
+pub async fn list_pdfs_in_folder(
+    registry: tauri::State<'_, ScanRegistry>,
+    folder_path: String,
+    scan_id: Option<String>,
+    options: Option<PdfScanOptions>,
+) -> Result<PdfListing, AppError> {
+    let path = Path::new(&folder_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
 
-    folders.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
-    Ok(folders)
+    let options = ScanOptions::new(options.unwrap_or_default());
+    if let Some(id) = &scan_id {
+        registry.begin(id)?;
+    }
+    let mut cancel = CancelCheck::new(&registry, scan_id.as_deref());
+    let result = scan_pdfs(path, &options, &mut cancel);
+    if let Some(id) = &scan_id {
+        registry.finish(id);
+    }
+    result
 }
 
 #[tauri::command]
-pub async fn list_pdfs_in_folder(folder_path: String) -> Result<Vec<PdfFile>, AppError> {
+pub async fn list_pdfs_in_folder_paged(
+    registry: tauri::State<'_, ScanRegistry>,
+    folder_path: String,
+    offset: usize,
+    limit: usize,
+    scan_id: Option<String>,
+    options: Option<PdfScanOptions>,
+) -> Result<PdfPage, AppError> {
     let path = Path::new(&folder_path);
     if !path.is_dir() {
         return Err(AppError::InvalidDirectory(folder_path));
     }
 
-    let mut pdfs = Vec::new();
-    collect_pdfs_recursive(path, &mut pdfs)?;
-    pdfs.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(pdfs)
+    // Walk the whole tree so `total` is exact; only the window is serialized back.
+    let options = ScanOptions::new(options.unwrap_or_default());
+    if let Some(id) = &scan_id {
+        registry.begin(id)?;
+    }
+    let mut cancel = CancelCheck::new(&registry, scan_id.as_deref());
+    let result = scan_pdfs(path, &options, &mut cancel);
+    if let Some(id) = &scan_id {
+        registry.finish(id);
+    }
+    let listing = result?;
+    let total = listing.files.len();
+    let files: Vec<PdfFile> = listing.files.into_iter().skip(offset).take(limit).collect();
+    let has_more = offset.saturating_add(files.len()) < total;
+
+    Ok(PdfPage { files, total, has_more, skipped: listing.skipped })
 }
 
-fn count_pdfs(dir: &Path) -> usize {
-    walkdir::WalkDir::new(dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "pdf"))
-        .count()
+/// Process folders under `root_path` with anything inside modified after
+/// `since` (RFC 3339), newest first. Uses the deepest mtime, so a PDF added to
+/// a subfolder counts even when the top-level folder's own mtime is unchanged.
+#[tauri::command]
+pub async fn list_changed_folders(
+    app: tauri::AppHandle,
+    registry: tauri::State<'_, ScanRegistry>,
+    root_path: String,
+    since: String,
+) -> Result<Vec<ProcessFolder>, AppError> {
+    let path = Path::new(&root_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(root_path));
+    }
+    let cutoff = DateTime::parse_from_rfc3339(&since)
+        .map_err(|e| AppError::InvalidArgument(format!("data {}: {}", since, e)))?
+        .with_timezone(&Utc);
+
+    let progress = ProgressEmitter::new(&app);
+    let options = FolderScanOptions::default();
+    let listing = scan_process_folders(path, &registry, None, &options, &progress)?;
+    let _ = app.emit("scan-complete", ScanComplete { total: listing.folders.len() });
+    Ok(modified_after(listing.folders, cutoff))
 }
 
-fn calculate_dir_size(dir: &Path) -> u64 {
-    walkdir::WalkDir::new(dir)
+/// Folders whose deepest mtime is after `cutoff`, newest first.
+fn modified_after(folders: Vec<ProcessFolder>, cutoff: DateTime<Utc>) -> Vec<ProcessFolder> {
+    // `latest_modified` is second-resolution, so compare at the same precision.
+    let cutoff = format_time(cutoff.into());
+    let mut changed: Vec<ProcessFolder> = folders
         .into_iter()
-        .filter_map(|e| e.ok())
-        .filter_map(|e| e.metadata().ok())
-        .filter(|m| m.is_file())
-        .map(|m| m.len())
-        .sum()
+        .filter(|folder| folder.latest_modified > cutoff)
+        .collect();
+    changed.sort_by(|a, b| b.latest_modified.cmp(&a.latest_modified));
+    changed
 }
 
-fn collect_pdfs_recursive(dir: &Path, pdfs: &mut Vec<PdfFile>) -> Result<(), AppError> {
-    for entry in fs::read_dir(dir)? {
+/// Recursively finds PDFs whose filename contains `query`, ignoring case.
+#[tauri::command]
+pub async fn search_pdfs(
+    folder_path: String,
+    query: String,
+    max_results: Option<usize>,
+) -> Result<Vec<PdfFile>, AppError> {
+    let path = Path::new(&folder_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+
+    let mut cancel = CancelCheck::never();
+    let mut options = ScanOptions::new(PdfScanOptions::default());
+    options.name_query = Some(query.to_lowercase());
+
+    let mut files = scan_pdfs(path, &options, &mut cancel)?.files;
+    if let Some(max) = max_results {
+        files.truncate(max);
+    }
+    Ok(files)
+}
+
+/// Groups PDFs with identical content. Files are bucketed by size first so only
+/// same-size candidates are hashed.
+#[tauri::command]
+pub async fn find_duplicate_pdfs(folder_path: String) -> Result<Vec<Vec<String>>, AppError> {
+    let path = Path::new(&folder_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+
+    let mut by_size: HashMap<u64, Vec<String>> = HashMap::new();
+    for pdf in find_pdfs(path)? {
+        by_size.entry(pdf.size_bytes).or_default().push(pdf.path);
+    }
+    let candidates: Vec<String> = by_size
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .collect();
+
+    let hashed: Vec<(String, Result<String, AppError>)> = candidates
+        .into_par_iter()
+        .map(|file| {
+            let hash = cache::hash_path(Path::new(&file), HashAlgorithm::Sha256);
+            (file, hash)
+        })
+        .collect();
+
+    let mut by_hash: HashMap<String, Vec<String>> = HashMap::new();
+    for (file, hash) in hashed {
+        by_hash.entry(hash?).or_default().push(file);
+    }
+
+    let mut duplicates: Vec<Vec<String>> = by_hash
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect();
+    duplicates.sort();
+    Ok(duplicates)
+}
+
+/// Moves a PDF to the OS recycle bin so the action stays recoverable.
+#[tauri::command]
+pub async fn trash_pdf(file_path: String) -> Result<(), AppError> {
+    let path = Path::new(&file_path);
+    if !path.exists() {
+        return Err(AppError::FileNotFound(file_path));
+    }
+    if !path.is_file() || matching_extension(path, PDF_ONLY).is_none() {
+        return Err(AppError::InvalidFile(file_path));
+    }
+
+    trash::delete(path)
+        .map_err(|e| AppError::IoError(e.to_string()))
+        .at_path(path)
+}
+
+/// Renames a process folder within its parent directory and returns the refreshed entry.
+#[tauri::command]
+pub async fn rename_folder(old_path: String, new_name: String) -> Result<ProcessFolder, AppError> {
+    let path = Path::new(&old_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(old_path));
+    }
+    validate_file_name(&new_name)?;
+
+    let parent = path
+        .parent()
+        .ok_or_else(|| AppError::InvalidDirectory(old_path.clone()))?;
+    let new_path = parent.join(&new_name);
+    if new_path.exists() {
+        return Err(AppError::AlreadyExists(new_path.to_string_lossy().to_string()));
+    }
+
+    fs::rename(path, &new_path).at_path(path)?;
+
+    let mut cancel = CancelCheck::never();
+    let options = ScanOptions::new(PdfScanOptions::default());
+    build_process_folder(&new_path, &options, &mut cancel)
+}
+
+/// Copies the PDF into `dest_folder`. When the name is taken, appends ` (1)`,
+/// ` (2)`, … before the extension instead of overwriting. The name is claimed
+/// with an exclusive create, so a file appearing concurrently is never
+/// clobbered; a failed copy removes the partial target.
+#[tauri::command]
+pub async fn copy_pdf(source_path: String, dest_folder: String) -> Result<PdfFile, AppError> {
+    let source = Path::new(&source_path);
+    if !source.is_file() {
+        return Err(AppError::FileNotFound(source_path));
+    }
+    if matching_extension(source, PDF_ONLY).is_none() {
+        return Err(AppError::InvalidFile(source_path));
+    }
+    let dest_dir = Path::new(&dest_folder);
+    if !dest_dir.is_dir() {
+        return Err(AppError::InvalidDirectory(dest_folder));
+    }
+
+    let (target, mut file) = reserve_name(dest_dir, source)?;
+    if let Err(e) = copy_into(source, &mut file) {
+        drop(file);
+        let _ = fs::remove_file(&target);
+        return Err(e).at_path(&target);
+    }
+    pdf_file_at(&target)
+}
+
+/// Streams `source` into an already-open target and gives it the source's permissions.
+fn copy_into(source: &Path, target: &mut fs::File) -> std::io::Result<()> {
+    let mut reader = fs::File::open(source)?;
+    std::io::copy(&mut reader, target)?;
+    target.set_permissions(reader.metadata()?.permissions())
+}
+
+/// Moves the PDF into `dest_folder`, suffixing the name like `copy_pdf` on a
+/// collision; a file already in `dest_folder` is returned untouched. The
+/// target name is claimed with an exclusive create and the rename then
+/// replaces that placeholder. A plain rename within a volume; across volumes
+/// the file is copied and the original deleted, and the copy is removed again
+/// if the original can't be. Cache rows are repointed to the new path so
+/// `prune_orphaned_cache` keeps them; failing that doesn't undo the move and
+/// is reported in the returned file's `warning`.
+#[tauri::command]
+pub async fn move_pdf(
+    app: tauri::AppHandle,
+    source_path: String,
+    dest_folder: String,
+) -> Result<PdfFile, AppError> {
+    let source = Path::new(&source_path);
+    if !source.is_file() {
+        return Err(AppError::FileNotFound(source_path));
+    }
+    if matching_extension(source, PDF_ONLY).is_none() {
+        return Err(AppError::InvalidFile(source_path));
+    }
+    let dest_dir = Path::new(&dest_folder);
+    if !dest_dir.is_dir() {
+        return Err(AppError::InvalidDirectory(dest_folder));
+    }
+
+    let target = relocate(source, dest_dir)?;
+    if target == source {
+        return pdf_file_at(source);
+    }
+    let repointed = cache::update_cached_path(&app, &source_path, &target.to_string_lossy());
+    moved_file(&target, repointed)
+}
+
+/// `PdfFile` for a file `move_pdf` just moved, carrying a failed cache
+/// repoint as its `warning`.
+fn moved_file(target: &Path, repointed: Result<usize, AppError>) -> Result<PdfFile, AppError> {
+    let mut file = pdf_file_at(target)?;
+    if let Err(e) = repointed {
+        file.warning = Some(format!("cache nao atualizado para o novo caminho: {}", e));
+    }
+    Ok(file)
+}
+
+/// Moves `source` into `dest_dir` under the first free name and returns where
+/// it ended up, which is `source` itself when it already lives in `dest_dir`.
+fn relocate(source: &Path, dest_dir: &Path) -> Result<PathBuf, AppError> {
+    let current_dir = source.parent().and_then(|parent| dunce::canonicalize(parent).ok());
+    if current_dir.is_some() && current_dir == dunce::canonicalize(dest_dir).ok() {
+        return Ok(source.to_path_buf());
+    }
+
+    // Windows can't rename over a file that is still open, so release the
+    // placeholder before replacing it.
+    let (target, placeholder) = reserve_name(dest_dir, source)?;
+    drop(placeholder);
+    let moved = match fs::rename(source, &target) {
+        Err(e) if is_cross_device(&e) => fs::OpenOptions::new()
+            .write(true)
+            .open(&target)
+            .and_then(|mut file| copy_into(source, &mut file))
+            .at_path(&target)
+            .and_then(|()| fs::remove_file(source).at_path(source)),
+        other => other.at_path(source),
+    };
+    if let Err(e) = moved {
+        let _ = fs::remove_file(&target);
+        return Err(e);
+    }
+
+    Ok(target)
+}
+
+/// Whether a rename failed only because source and target are on different
+/// volumes (EXDEV on Unix, ERROR_NOT_SAME_DEVICE on Windows).
+fn is_cross_device(err: &std::io::Error) -> bool {
+    let code = if cfg!(windows) { 17 } else { 18 };
+    err.raw_os_error() == Some(code)
+}
+
+/// Creates the first free name in `dir` derived from `source`, so no other
+/// writer can take it between the check and the write. Names that turn out
+/// to exist are skipped; any other error is returned.
+fn reserve_name(dir: &Path, source: &Path) -> Result<(PathBuf, fs::File), AppError> {
+    for candidate in candidate_names(dir, source)? {
+        match fs::OpenOptions::new().write(true).create_new(true).open(&candidate) {
+            Ok(file) => return Ok((candidate, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e).at_path(&candidate),
+        }
+    }
+    Err(AppError::AlreadyExists(dir.to_string_lossy().to_string()))
+}
+
+/// `name.pdf`, then `name (1).pdf`, `name (2).pdf`, … inside `dir`.
+pub(crate) fn candidate_names(
+    dir: &Path,
+    source: &Path,
+) -> Result<impl Iterator<Item = PathBuf>, AppError> {
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| AppError::InvalidFile(source.to_string_lossy().to_string()))?;
+    let first = dir.join(file_name);
+
+    let stem = source.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let extension = source.extension().map(|ext| format!(".{}", ext.to_string_lossy()));
+    let dir = dir.to_path_buf();
+    let suffixed = (1..u32::MAX).map(move |n| {
+        dir.join(format!("{} ({}){}", stem, n, extension.as_deref().unwrap_or("")))
+    });
+    Ok(std::iter::once(first).chain(suffixed))
+}
+
+/// Rejects names that would escape the parent directory or that some platform can't store.
+fn validate_file_name(name: &str) -> Result<(), AppError> {
+    const INVALID_CHARS: &[char] = &['/', '\\', '<', '>', ':', '"', '|', '?', '*'];
+
+    let invalid = name.trim().is_empty()
+        || name == "."
+        || name == ".."
+        || name.ends_with('.')
+        || name.ends_with(' ')
+        || name.chars().any(|c| c.is_control() || INVALID_CHARS.contains(&c));
+    if invalid {
+        return Err(AppError::InvalidName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Recursive size of each immediate subdirectory, plus the files sitting directly
+/// in `folder_path` under the name `.`, sorted largest first. Subdirectories
+/// are measured in parallel. Hidden entries are counted, since they take space.
+#[tauri::command]
+pub async fn folder_size_breakdown(folder_path: String) -> Result<Vec<(String, u64)>, AppError> {
+    let path = Path::new(&folder_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+
+    let mut subdirs = Vec::new();
+    let mut direct_bytes = 0;
+    for entry in fs::read_dir(path).at_path(path)?.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            subdirs.push(entry.path());
+        } else if file_type.is_file() {
+            direct_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+
+    let options =
+        ScanOptions::new(PdfScanOptions { skip_hidden: false, ..PdfScanOptions::default() });
+    let mut breakdown = subdirs
+        .par_iter()
+        .map(|dir| {
+            let mut cancel = CancelCheck::never();
+            let summary = summarize_dir(dir, &options, &mut cancel)?;
+            let name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            Ok((name, summary.total_size_bytes))
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+    breakdown.push((".".to_string(), direct_bytes));
+
+    breakdown.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(breakdown)
+}
+
+/// True when another program holds the file in a way that stops us reading it:
+/// a share-mode lock on Windows, an exclusive advisory lock elsewhere. Probing
+/// only opens the file for reading and never modifies it.
+#[tauri::command]
+pub async fn is_file_locked(file_path: String) -> Result<bool, AppError> {
+    let path = Path::new(&file_path);
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            return match AppError::from(e) {
+                AppError::FileLocked(_) => Ok(true),
+                other => Err(other.at_path(path)),
+            }
+        }
+    };
+
+    match fs2::FileExt::try_lock_shared(&file) {
+        Ok(()) => {
+            let _ = fs2::FileExt::unlock(&file);
+            Ok(false)
+        }
+        Err(e) if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => Ok(true),
+        Err(e) => Err(AppError::from(e).at_path(path)),
+    }
+}
+
+/// Writes a CSV of every PDF under `folder_path` with columns name, path,
+/// size_bytes, last_modified, page_count and extraction_status, returning the
+/// number of rows. Statuses come from this session's extractions.
+#[tauri::command]
+pub async fn export_folder_listing(
+    app: tauri::AppHandle,
+    folder_path: String,
+    output_path: String,
+) -> Result<usize, AppError> {
+    let path = Path::new(&folder_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+    let extracted = cache::extracted_hashes(&app)?;
+    let extractions = app.state::<ExtractionRegistry>();
+    write_folder_listing(path, Path::new(&output_path), &extractions, &extracted)
+}
+
+fn write_folder_listing(
+    path: &Path,
+    output: &Path,
+    extractions: &ExtractionRegistry,
+    extracted: &HashSet<String>,
+) -> Result<usize, AppError> {
+    let options = ScanOptions::new(PdfScanOptions { with_page_count: true, ..Default::default() });
+    let mut cancel = CancelCheck::never();
+    let listing = scan_pdfs(path, &options, &mut cancel)?;
+
+    let csv_error = |e: csv::Error| AppError::IoError(format!("{}: {}", output.display(), e));
+    let mut writer = csv::Writer::from_path(output).map_err(csv_error)?;
+    writer
+        .write_record(["name", "path", "size_bytes", "last_modified", "page_count", "extraction_status"])
+        .map_err(csv_error)?;
+
+    for file in &listing.files {
+        let status = match extractions.reported_status(&file.full_path, extracted) {
+            ExtractionStatus::Pending => "Pending".to_string(),
+            ExtractionStatus::InProgress => "InProgress".to_string(),
+            ExtractionStatus::Completed => "Completed".to_string(),
+            ExtractionStatus::NeedsOcr => "NeedsOcr".to_string(),
+            ExtractionStatus::Failed(error) => format!("Failed: {}", error),
+        };
+        writer
+            .write_record([
+                file.name.clone(),
+                file.full_path.clone(),
+                file.size_bytes.to_string(),
+                file.last_modified.clone(),
+                file.page_count.map(|count| count.to_string()).unwrap_or_default(),
+                status,
+            ])
+            .map_err(csv_error)?;
+    }
+    writer.flush().at_path(output)?;
+    Ok(listing.files.len())
+}
+
+/// Canonicalizes the selected folders, resolving symlinks, and drops
+/// duplicates and any folder nested inside another selected one. The result
+/// keeps the order in which the survivors were first selected. On Windows the
+/// `\\?\` verbatim prefix is left off wherever a plain path can express it.
+#[tauri::command]
+pub async fn normalize_folder_selection(paths: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut canonical: Vec<PathBuf> = Vec::with_capacity(paths.len());
+    for raw in &paths {
+        let path = Path::new(raw);
+        if !path.is_dir() {
+            return Err(AppError::InvalidDirectory(raw.clone()));
+        }
+        let resolved = dunce::canonicalize(path).at_path(path)?;
+        if !canonical.contains(&resolved) {
+            canonical.push(resolved);
+        }
+    }
+
+    let selection = canonical
+        .iter()
+        .filter(|path| {
+            !canonical
+                .iter()
+                .any(|other| other != *path && path.starts_with(other))
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    Ok(selection)
+}
+
+/// Largest range `read_file_range` returns in one call.
+const MAX_RANGE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Reads `length` bytes starting at `offset`, for viewers that load a PDF
+/// lazily. `length` is clamped to the end of the file; an offset past the end
+/// or a `length` over 16 MiB is rejected with `InvalidArgument`. The bytes go
+/// back as a raw IPC body, so the frontend receives an `ArrayBuffer` instead
+/// of a JSON array of numbers.
+#[tauri::command]
+pub async fn read_file_range(
+    file_path: String,
+    offset: u64,
+    length: u64,
+) -> Result<tauri::ipc::Response, AppError> {
+    let bytes = read_range(Path::new(&file_path), offset, length)?;
+    Ok(tauri::ipc::Response::new(bytes))
+}
+
+fn read_range(path: &Path, offset: u64, length: u64) -> Result<Vec<u8>, AppError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    if length > MAX_RANGE_BYTES {
+        return Err(AppError::InvalidArgument(format!(
+            "length {} acima do limite de {} bytes",
+            length, MAX_RANGE_BYTES
+        )));
+    }
+    let mut file = fs::File::open(path).at_path(path)?;
+    let file_len = file.metadata().at_path(path)?.len();
+    if offset > file_len {
+        return Err(AppError::InvalidArgument(format!(
+            "offset {} alem do fim do arquivo ({} bytes)",
+            offset, file_len
+        ))
+        .at_path(path));
+    }
+
+    let length = length.min(file_len - offset);
+    let mut buffer = Vec::with_capacity(length as usize);
+    file.seek(SeekFrom::Start(offset)).at_path(path)?;
+    file.take(length).read_to_end(&mut buffer).at_path(path)?;
+    Ok(buffer)
+}
+
+/// Opens the file's parent directory in Finder/Explorer/the desktop file
+/// manager, selecting the file where the platform supports it.
+#[tauri::command]
+pub async fn reveal_in_file_manager(app: tauri::AppHandle, file_path: String) -> Result<(), AppError> {
+    let path = Path::new(&file_path);
+    if !path.exists() {
+        return Err(AppError::FileNotFound(file_path));
+    }
+
+    app.opener()
+        .reveal_item_in_dir(path)
+        .map_err(|e| AppError::IoError(e.to_string()))
+        .at_path(path)
+}
+
+/// Emits throttled `scan-progress` events from the parallel folder workers.
+struct ProgressEmitter<'a> {
+    emit: Box<dyn Fn(ScanProgress) + Send + Sync + 'a>,
+    processed: AtomicUsize,
+    last_emit: Mutex<Option<Instant>>,
+}
+
+impl<'a> ProgressEmitter<'a> {
+    fn new(app: &'a tauri::AppHandle) -> Self {
+        Self::with_sink(move |progress| {
+            let _ = app.emit("scan-progress", progress);
+        })
+    }
+
+    /// Hands each throttled update to `emit` instead of sending an event.
+    fn with_sink(emit: impl Fn(ScanProgress) + Send + Sync + 'a) -> Self {
+        Self { emit: Box::new(emit), processed: AtomicUsize::new(0), last_emit: Mutex::new(None) }
+    }
+
+    fn folder_done(&self, folder_name: &str) {
+        let processed = self.processed.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut last_emit = self.last_emit.lock().unwrap();
+        if last_emit.is_none_or(|t| t.elapsed() >= PROGRESS_THROTTLE) {
+            (self.emit)(ScanProgress { processed, current_folder: folder_name.to_string() });
+            *last_emit = Some(Instant::now());
+        }
+    }
+}
+
+/// PDF count and total byte size gathered in one walk of a folder.
+struct DirSummary {
+    pdf_count: usize,
+    total_size_bytes: u64,
+    /// Newest mtime among the directory and its descendants.
+    latest_modified: Option<std::time::SystemTime>,
+}
+
+/// Summarizes the subfolders of `root`, reporting each finished folder to
+/// `progress`. Callers emit `scan-complete` once they have the final listing.
+fn scan_process_folders(
+    root: &Path,
+    registry: &ScanRegistry,
+    scan_id: Option<&str>,
+    options: &FolderScanOptions,
+    progress: &ProgressEmitter,
+) -> Result<ProcessFolderListing, AppError> {
+    let skip_hidden = options.skip_hidden;
+    let scan_options = ScanOptions::for_folders(options);
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(root).at_path(root)? {
         let entry = entry?;
+        if skip_hidden && is_hidden_name(&entry.file_name()) {
+            continue;
+        }
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            dirs.push(entry_path);
+        }
+    }
+
+    let mut folders = dirs
+        .par_iter()
+        .map(|dir| {
+            // Each task counts visits from zero, so small folders would never
+            // reach a periodic check; look once up front.
+            let mut cancel = CancelCheck::new(registry, scan_id);
+            cancel.check()?;
+            let folder = build_process_folder(dir, &scan_options, &mut cancel)?;
+            progress.folder_done(&folder.name);
+            Ok(folder)
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    // Filter before reporting so excluded folders never count toward totals.
+    folders.retain(|folder| folder.pdf_count >= options.min_pdf_count);
+
+    let summary = FolderSummary {
+        total_folders: folders.len(),
+        total_pdfs: folders.iter().map(|f| f.pdf_count).sum(),
+        total_bytes: folders.iter().map(|f| f.total_size_bytes).sum(),
+    };
+    Ok(ProcessFolderListing { folders, summary })
+}
+
+fn sort_folders(folders: &mut [ProcessFolder], mode: FolderSortMode, descending: bool) {
+    folders.sort_by(|a, b| {
+        let ordering = match mode {
+            FolderSortMode::Name => a.name.cmp(&b.name),
+            FolderSortMode::LastModified => a.last_modified.cmp(&b.last_modified),
+            FolderSortMode::PdfCount => a.pdf_count.cmp(&b.pdf_count),
+            FolderSortMode::TotalSize => a.total_size_bytes.cmp(&b.total_size_bytes),
+        };
+        if descending { ordering.reverse() } else { ordering }
+    });
+}
+
+fn build_process_folder(
+    dir: &Path,
+    options: &ScanOptions,
+    cancel: &mut CancelCheck,
+) -> Result<ProcessFolder, AppError> {
+    let summary = summarize_dir(dir, options, cancel)?;
+    let metadata = fs::metadata(dir).at_path(dir)?;
+    let modified = metadata.modified()?;
+
+    Ok(ProcessFolder {
+        path: dir.to_string_lossy().to_string(),
+        name: dir.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        root: dir.parent().unwrap_or(dir).to_string_lossy().to_string(),
+        pdf_count: summary.pdf_count,
+        total_size_bytes: summary.total_size_bytes,
+        size_display: format_size(summary.total_size_bytes),
+        last_modified: format_time(modified),
+        latest_modified: format_time(summary.latest_modified.unwrap_or(modified).max(modified)),
+        created: metadata.created().ok().map(format_time),
+    })
+}
+
+/// Every PDF under `dir` with default options, for commands that process a whole folder.
+pub(crate) fn find_pdfs(dir: &Path) -> Result<Vec<PdfFile>, AppError> {
+    let mut cancel = CancelCheck::never();
+    let options = ScanOptions::new(PdfScanOptions::default());
+    Ok(scan_pdfs(dir, &options, &mut cancel)?.files)
+}
+
+/// Recursively collects every PDF under `dir`, sorted by name so paging is stable.
+/// Unreadable entries are recorded in `skipped` instead of aborting the scan.
+fn scan_pdfs(
+    dir: &Path,
+    options: &ScanOptions,
+    cancel: &mut CancelCheck,
+) -> Result<PdfListing, AppError> {
+    let mut listing = PdfListing { files: Vec::new(), skipped: Vec::new() };
+    let mut visited = HashSet::from([fs::canonicalize(dir)?]);
+    collect_pdfs_recursive(dir, 0, &mut listing, &mut visited, options, cancel)?;
+    if options.natural_sort {
+        listing.files.sort_by(|a, b| natural_cmp(&a.name, &b.name));
+    } else {
+        listing.files.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    if let Some(prefix) = &options.relative_to {
+        for file in &mut listing.files {
+            if let Ok(relative) = Path::new(&file.full_path).strip_prefix(prefix) {
+                file.path = relative.to_string_lossy().to_string();
+            }
+        }
+    }
+    Ok(listing)
+}
+
+/// Totals for everything under `dir`. Files count toward `pdf_count` by the
+/// same extension match the listings use.
+fn summarize_dir(
+    dir: &Path,
+    options: &ScanOptions,
+    cancel: &mut CancelCheck,
+) -> Result<DirSummary, AppError> {
+    let skip_hidden = options.skip_hidden;
+    let mut summary = DirSummary { pdf_count: 0, total_size_bytes: 0, latest_modified: None };
+    let walker = walkdir::WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !skip_hidden || !is_hidden_name(e.file_name()));
+    for entry in walker.filter_map(|e| e.ok()) {
+        cancel.tick()?;
+        let metadata = entry.metadata().ok();
+        if let Some(modified) = metadata.as_ref().and_then(|m| m.modified().ok()) {
+            summary.latest_modified = summary.latest_modified.max(Some(modified));
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if matching_extension(entry.path(), &options.extensions).is_some() {
+            summary.pdf_count += 1;
+        }
+        if let Some(metadata) = metadata {
+            summary.total_size_bytes += metadata.len();
+        }
+    }
+    Ok(summary)
+}
+
+fn collect_pdfs_recursive(
+    dir: &Path,
+    depth: usize,
+    listing: &mut PdfListing,
+    visited: &mut HashSet<PathBuf>,
+    options: &ScanOptions,
+    cancel: &mut CancelCheck,
+) -> Result<(), AppError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        // The scanned folder itself must be readable; anything below it is skipped.
+        Err(err) if depth == 0 => return Err(AppError::from(err).at_path(dir)),
+        Err(err) => {
+            listing.skip(dir, err);
+            return Ok(());
+        }
+    };
+
+    for entry in entries {
+        cancel.tick()?;
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                listing.skip(dir, err);
+                continue;
+            }
+        };
         let path = entry.path();
 
+        if options.skip_hidden && is_hidden_name(&entry.file_name()) {
+            continue;
+        }
+
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_symlink() && !options.follow_symlinks => continue,
+            Ok(_) => {}
+            Err(err) => {
+                listing.skip(&path, err);
+                continue;
+            }
+        }
+
         if path.is_dir() {
-            collect_pdfs_recursive(&path, pdfs)?;
-        } else if path.extension().map_or(false, |ext| ext == "pdf") {
-            let metadata = entry.metadata()?;
-            pdfs.push(PdfFile {
-                path: path.to_string_lossy().to_string(),
-                name: entry.file_name().to_string_lossy().to_string(),
-                size_bytes: metadata.len(),
-                last_modified: format_time(metadata.modified()?),
-                extracted_text: None,
-                extraction_status: ExtractionStatus::Pending,
-            });
+            if options.max_depth.is_some_and(|max| depth >= max) {
+                continue;
+            }
+            // A symlink pointing back up the tree would otherwise recurse forever.
+            match fs::canonicalize(&path) {
+                Ok(canonical) => {
+                    if visited.insert(canonical) {
+                        collect_pdfs_recursive(&path, depth + 1, listing, visited, options, cancel)?;
+                    }
+                }
+                Err(err) => listing.skip(&path, err),
+            }
+        } else if let Some((extension, is_real_pdf)) = listed_extension(&path, options) {
+            if !matches_name_query(&entry.file_name().to_string_lossy(), options) {
+                continue;
+            }
+            match build_pdf_file(&entry, extension, is_real_pdf, options) {
+                Ok(pdf) => listing.files.push(pdf),
+                Err(err) => listing.skip(&path, err),
+            }
         }
     }
     Ok(())
 }
 
-fn format_time(time: std::time::SystemTime) -> String {
+fn build_pdf_file(
+    entry: &fs::DirEntry,
+    extension: String,
+    is_real_pdf: Option<bool>,
+    options: &ScanOptions,
+) -> Result<PdfFile, AppError> {
+    let metadata = entry.metadata()?;
+    // A corrupt file just leaves the count empty rather than failing the listing.
+    let page_count = if options.with_page_count && is_real_pdf.unwrap_or(extension == "pdf") {
+        pdf::page_count(&entry.path())
+    } else {
+        None
+    };
+
+    Ok(PdfFile {
+        path: entry.path().to_string_lossy().to_string(),
+        full_path: entry.path().to_string_lossy().to_string(),
+        raw_path: raw_path_bytes(&entry.path()),
+        is_real_pdf,
+        name: entry.file_name().to_string_lossy().to_string(),
+        extension,
+        size_bytes: metadata.len(),
+        size_display: format_size(metadata.len()),
+        last_modified: format_time(metadata.modified()?),
+        created: metadata.created().ok().map(format_time),
+        page_count,
+        extracted_text: None,
+        extraction_status: ExtractionStatus::Pending,
+        warning: None,
+    })
+}
+
+/// `PdfFile` for a single file outside of a scan, e.g. one the app just wrote.
+pub(crate) fn pdf_file_at(path: &Path) -> Result<PdfFile, AppError> {
+    let metadata = fs::metadata(path).at_path(path)?;
+    Ok(PdfFile {
+        path: path.to_string_lossy().to_string(),
+        full_path: path.to_string_lossy().to_string(),
+        raw_path: raw_path_bytes(path),
+        is_real_pdf: None,
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        extension: path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default(),
+        size_bytes: metadata.len(),
+        size_display: format_size(metadata.len()),
+        last_modified: format_time(metadata.modified().at_path(path)?),
+        created: metadata.created().ok().map(format_time),
+        page_count: matching_extension(path, PDF_ONLY).and_then(|_| pdf::page_count(path)),
+        extracted_text: None,
+        extraction_status: ExtractionStatus::Pending,
+        warning: None,
+    })
+}
+
+/// Native bytes of `path` when it isn't valid UTF-8, `None` otherwise. Unix
+/// yields the raw bytes; Windows the UTF-16 units, little-endian.
+pub(crate) fn raw_path_bytes(path: &Path) -> Option<Vec<u8>> {
+    if path.to_str().is_some() {
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Some(path.as_os_str().as_bytes().to_vec())
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        Some(path.as_os_str().encode_wide().flat_map(u16::to_le_bytes).collect())
+    }
+}
+
+/// Inverse of `raw_path_bytes`.
+pub(crate) fn path_from_raw(raw: Vec<u8>) -> Result<PathBuf, AppError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Ok(PathBuf::from(std::ffi::OsString::from_vec(raw)))
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStringExt;
+        if raw.len() % 2 != 0 {
+            return Err(AppError::InvalidPathEncoding(format!("{} bytes", raw.len())));
+        }
+        let units: Vec<u16> = raw.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect();
+        Ok(PathBuf::from(std::ffi::OsString::from_wide(&units)))
+    }
+}
+
+/// Resolves a path received from the frontend. `raw_path` wins when given, so
+/// files listed with a lossy name can still be opened. A lossy string on its
+/// own can't name the file, which is reported as `InvalidPathEncoding`.
+pub(crate) fn resolve_path(file_path: &str, raw_path: Option<Vec<u8>>) -> Result<PathBuf, AppError> {
+    if let Some(raw) = raw_path {
+        return path_from_raw(raw);
+    }
+    let path = PathBuf::from(file_path);
+    if file_path.contains(char::REPLACEMENT_CHARACTER) && !path.exists() {
+        return Err(AppError::InvalidPathEncoding(file_path.to_string()));
+    }
+    Ok(path)
+}
+
+/// Lowercases and strips leading dots; an empty selection means PDFs only.
+fn normalize_extensions(extensions: Vec<String>) -> Vec<String> {
+    let normalized: Vec<String> = extensions
+        .iter()
+        .map(|ext| ext.trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect();
+
+    if normalized.is_empty() {
+        vec!["pdf".to_string()]
+    } else {
+        normalized
+    }
+}
+
+/// Orders strings with runs of ASCII digits compared by numeric value, so
+/// `doc2.pdf` sorts before `doc10.pdf`. Equal values with different zero
+/// padding fall back to the shorter run first, keeping the order total.
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_len = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+                let b_len = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+                let (a_run, b_run) = (&a[..a_len], &b[..b_len]);
+                let (a_num, b_num) = (a_run.trim_start_matches('0'), b_run.trim_start_matches('0'));
+                let ordering = a_num
+                    .len()
+                    .cmp(&b_num.len())
+                    .then_with(|| a_num.cmp(b_num))
+                    .then_with(|| a_run.len().cmp(&b_run.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[a_len..];
+                b = &b[b_len..];
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a = &a[x.len_utf8()..];
+                b = &b[y.len_utf8()..];
+            }
+        }
+    }
+}
+
+fn matches_name_query(name: &str, options: &ScanOptions) -> bool {
+    options
+        .name_query
+        .as_ref()
+        .is_none_or(|query| name.to_lowercase().contains(query.as_str()))
+}
+
+/// Dot-files and folders plus junk directories left by archive tools.
+fn is_hidden_name(name: &std::ffi::OsStr) -> bool {
+    let name = name.to_string_lossy();
+    name.starts_with('.') || name == "__MACOSX" || name == "$RECYCLE.BIN"
+}
+
+/// Extension to list the file under, plus `is_real_pdf` in `verify_magic`
+/// mode, or `None` to leave it out. Without verification this is plain
+/// extension matching.
+fn listed_extension(path: &Path, options: &ScanOptions) -> Option<(String, Option<bool>)> {
+    let matched = matching_extension(path, &options.extensions);
+    if !options.verify_magic {
+        return matched.map(|ext| (ext, None));
+    }
+
+    let real = has_pdf_magic(path);
+    match matched {
+        Some(ext) if ext != "pdf" => Some((ext, Some(real))),
+        _ if real && options.extensions.iter().any(|ext| ext == "pdf") => {
+            let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+            Some((ext, Some(true)))
+        }
+        _ => None,
+    }
+}
+
+/// True when the file begins with the `%PDF-` signature.
+pub(crate) fn has_pdf_magic(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut header = [0u8; 5];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map(|_| &header == b"%PDF-")
+        .unwrap_or(false)
+}
+
+/// Extensions matched when a command only deals with PDFs.
+pub(crate) const PDF_ONLY: &[&str] = &["pdf"];
+
+/// Lowercased extension of `path` if it is one of `extensions` (lowercase,
+/// no dot). Listing, counting and the single-file commands all match through
+/// here, so `SCAN001.PDF` and `c.Pdf` are treated the same as `a.pdf`.
+pub(crate) fn matching_extension<S: AsRef<str>>(path: &Path, extensions: &[S]) -> Option<String> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    extensions.iter().any(|candidate| candidate.as_ref() == ext).then_some(ext)
+}
+
+/// Human-readable size using 1024-based units, e.g. `512 B`, `1.4 MB`.
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    // Compare the rounded value so 1_048_575 B reads "1.0 MB", not "1024.0 KB".
+    while (value * 10.0).round() >= 10240.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+pub(crate) fn format_time(time: std::time::SystemTime) -> String {
     let datetime: DateTime<Utc> = time.into();
     datetime.format("%Y-%m-%dT%H:%M:%SZ").to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &[u8]) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn scan(dir: &Path, options: PdfScanOptions) -> PdfListing {
+        let mut cancel = CancelCheck::never();
+        scan_pdfs(dir, &ScanOptions::new(options), &mut cancel).unwrap()
+    }
+
+    fn names(listing: &PdfListing) -> Vec<&str> {
+        listing.files.iter().map(|file| file.name.as_str()).collect()
+    }
+
+    #[test]
+    fn pdf_extensions_match_in_any_case() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.pdf", "B.PDF", "c.Pdf", "d.txt"] {
+            write(&dir.path().join(name), b"%PDF-1.4");
+        }
+        let listing = scan(dir.path(), PdfScanOptions::default());
+        assert_eq!(names(&listing), ["B.PDF", "a.pdf", "c.Pdf"]);
+        assert!(listing.files.iter().all(|file| file.extension == "pdf"));
+    }
+
+    #[test]
+    fn counting_and_listing_match_the_same_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("a.pdf"), b"%PDF-1.4");
+        write(&dir.path().join("B.PDF"), b"%PDF-1.4");
+        write(&dir.path().join("sub/c.tif"), b"II*");
+        write(&dir.path().join("notes.txt"), b"x");
+
+        for extensions in [vec![], vec!["pdf".to_string(), ".TIF".to_string()]] {
+            let options =
+                ScanOptions::new(PdfScanOptions { extensions, ..PdfScanOptions::default() });
+            let mut cancel = CancelCheck::never();
+            let listed = scan_pdfs(dir.path(), &options, &mut cancel).unwrap().files.len();
+            let counted = summarize_dir(dir.path(), &options, &mut cancel).unwrap().pdf_count;
+            assert_eq!(listed, counted);
+        }
+    }
+
+    #[test]
+    fn matching_extension_ignores_case() {
+        assert_eq!(matching_extension(Path::new("SCAN001.PDF"), PDF_ONLY), Some("pdf".to_string()));
+        assert_eq!(matching_extension(Path::new("c.Pdf"), PDF_ONLY), Some("pdf".to_string()));
+        assert_eq!(matching_extension(Path::new("c.pdf.txt"), PDF_ONLY), None);
+        assert_eq!(matching_extension(Path::new("pdf"), PDF_ONLY), None);
+    }
+
+    #[test]
+    fn format_size_picks_the_unit_after_rounding() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.0 KB");
+        assert_eq!(format_size(1_048_575), "1.0 MB");
+        assert_eq!(format_size(1_048_576), "1.0 MB");
+        assert_eq!(format_size(1_073_741_823), "1.0 GB");
+        assert_eq!(format_size(1_468_006), "1.4 MB");
+        assert_eq!(format_size(u64::MAX), "16384.0 PB");
+    }
+
+    #[test]
+    fn cancel_only_affects_running_scans() {
+        let registry = ScanRegistry::default();
+        registry.cancel("early");
+        registry.begin("early").unwrap();
+        assert!(!registry.is_cancelled("early"), "a cancel sent before the scan started leaked");
+
+        registry.cancel("early");
+        assert!(registry.is_cancelled("early"));
+        registry.finish("early");
+        assert!(!registry.is_cancelled("early"));
+    }
+
+    #[test]
+    fn check_sees_cancel_before_first_interval() {
+        let registry = ScanRegistry::default();
+        registry.begin("scan").unwrap();
+        registry.cancel("scan");
+
+        let mut cancel = CancelCheck::new(&registry, Some("scan"));
+        assert!(cancel.tick().is_ok(), "tick only consults the registry every interval");
+        assert!(matches!(cancel.check(), Err(AppError::Cancelled(_))));
+    }
+
+    #[test]
+    fn reused_scan_id_starts_uncancelled() {
+        let registry = ScanRegistry::default();
+        registry.begin("scan").unwrap();
+        registry.cancel("scan");
+        registry.finish("scan");
+        registry.cancel("scan");
+
+        registry.begin("scan").unwrap();
+        assert!(!registry.is_cancelled("scan"));
+    }
+
+    #[test]
+    fn an_id_still_running_cannot_start_a_second_scan() {
+        let registry = ScanRegistry::default();
+        registry.begin("scan").unwrap();
+        let err = registry.begin("scan").unwrap_err();
+        assert_eq!(err.code(), "INVALID_ARGUMENT");
+
+        // The rejected scan never ran, so the first one keeps its own cancel flag.
+        registry.cancel("scan");
+        assert!(registry.is_cancelled("scan"));
+        registry.finish("scan");
+        registry.begin("scan").unwrap();
+        assert!(!registry.is_cancelled("scan"));
+    }
+
+    fn empty_listing() -> ProcessFolderListing {
+        let summary = FolderSummary { total_folders: 0, total_pdfs: 0, total_bytes: 0 };
+        ProcessFolderListing { folders: Vec::new(), summary }
+    }
+
+    #[test]
+    fn unreadable_roots_are_skipped() {
+        let merged = merge_root_listings(vec![
+            ("/a".to_string(), Ok(empty_listing())),
+            ("/b".to_string(), Err(AppError::InvalidDirectory("/b".to_string()))),
+        ])
+        .unwrap();
+        assert_eq!(merged.skipped.len(), 1);
+        assert_eq!(merged.skipped[0].0, "/b");
+    }
+
+    #[test]
+    fn a_cancelled_root_cancels_the_whole_listing() {
+        let merged = merge_root_listings(vec![
+            ("/a".to_string(), Ok(empty_listing())),
+            ("/b".to_string(), Err(AppError::Cancelled("scan".to_string()))),
+        ]);
+        assert!(matches!(merged, Err(AppError::Cancelled(_))));
+    }
+
+    #[tokio::test]
+    async fn selection_drops_duplicates_and_nested_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("clientes");
+        let nested = root.join("processo");
+        fs::create_dir_all(&nested).unwrap();
+        let other = dir.path().join("outros");
+        fs::create_dir_all(&other).unwrap();
+
+        let raw = |path: &Path| path.to_string_lossy().to_string();
+        let selection = normalize_folder_selection(vec![
+            raw(&nested),
+            raw(&root),
+            raw(&root.join("processo").join("..")),
+            raw(&other),
+        ])
+        .await
+        .unwrap();
+        let expected: Vec<String> = [&root, &other]
+            .iter()
+            .map(|path| raw(&dunce::canonicalize(path).unwrap()))
+            .collect();
+        assert_eq!(selection, expected);
+        assert!(selection.iter().all(|path| !path.starts_with(r"\\?\")));
+    }
+
+    #[tokio::test]
+    async fn copy_suffixes_taken_names_instead_of_overwriting() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("origem").join("peticao.pdf");
+        write(&source, b"%PDF-1.4 nova");
+        let dest = dir.path().join("destino");
+        write(&dest.join("peticao.pdf"), b"%PDF-1.4 antiga");
+        write(&dest.join("peticao (1).pdf"), b"%PDF-1.4 antiga");
+
+        let raw = |path: &Path| path.to_string_lossy().to_string();
+        let copied = copy_pdf(raw(&source), raw(&dest)).await.unwrap();
+        assert_eq!(copied.path, raw(&dest.join("peticao (2).pdf")));
+        assert_eq!(fs::read(dest.join("peticao (2).pdf")).unwrap(), b"%PDF-1.4 nova");
+        assert_eq!(fs::read(dest.join("peticao.pdf")).unwrap(), b"%PDF-1.4 antiga");
+    }
+
+    #[test]
+    fn reserving_a_name_creates_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = Path::new("/elsewhere/ata.pdf");
+        let (first, _) = reserve_name(dir.path(), source).unwrap();
+        let (second, _) = reserve_name(dir.path(), source).unwrap();
+        assert_eq!(first, dir.path().join("ata.pdf"));
+        assert_eq!(second, dir.path().join("ata (1).pdf"));
+        assert!(first.exists() && second.exists());
+    }
+
+    #[test]
+    fn move_into_the_current_folder_keeps_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("ata.pdf");
+        write(&source, b"%PDF-1.4");
+
+        assert_eq!(relocate(&source, dir.path()).unwrap(), source);
+        assert!(source.exists());
+        assert!(!dir.path().join("ata (1).pdf").exists());
+    }
+
+    #[test]
+    fn move_suffixes_taken_names_instead_of_overwriting() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("origem").join("ata.pdf");
+        write(&source, b"%PDF-1.4 nova");
+        let dest = dir.path().join("destino");
+        write(&dest.join("ata.pdf"), b"%PDF-1.4 antiga");
+
+        let target = relocate(&source, &dest).unwrap();
+        assert_eq!(target, dest.join("ata (1).pdf"));
+        assert_eq!(fs::read(&target).unwrap(), b"%PDF-1.4 nova");
+        assert_eq!(fs::read(dest.join("ata.pdf")).unwrap(), b"%PDF-1.4 antiga");
+        assert!(!source.exists());
+    }
+
+    #[test]
+    fn a_failed_cache_repoint_is_reported_on_the_moved_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("ata.pdf");
+        write(&target, b"%PDF-1.4");
+
+        assert!(moved_file(&target, Ok(1)).unwrap().warning.is_none());
+        let failed = Err(AppError::DatabaseError("database is locked".into()));
+        let warning = moved_file(&target, failed).unwrap().warning.unwrap();
+        assert!(warning.contains("database is locked"), "{}", warning);
+    }
+
+    #[test]
+    fn ranges_are_clamped_to_the_end_of_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.pdf");
+        write(&path, b"%PDF-1.4 conteudo");
+        assert_eq!(read_range(&path, 1, 3).unwrap(), b"PDF");
+        assert_eq!(read_range(&path, 9, 1000).unwrap(), b"conteudo");
+        assert!(read_range(&path, 17, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ranges_past_the_end_or_over_the_cap_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.pdf");
+        write(&path, b"%PDF-1.4");
+        assert_eq!(read_range(&path, 9, 1).unwrap_err().code(), "INVALID_ARGUMENT");
+        let oversized = read_range(&path, 0, MAX_RANGE_BYTES + 1).unwrap_err();
+        assert_eq!(oversized.code(), "INVALID_ARGUMENT");
+        assert_eq!(read_range(&path, 0, MAX_RANGE_BYTES).unwrap(), b"%PDF-1.4");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_cycles_end_the_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("autos/peca.pdf"), b"%PDF-1.4");
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("autos/volta")).unwrap();
+
+        let options = PdfScanOptions { follow_symlinks: true, ..PdfScanOptions::default() };
+        assert_eq!(names(&scan(dir.path(), options)), ["peca.pdf"]);
+    }
+
+    #[test]
+    fn max_depth_stops_descending() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut level = dir.path().to_path_buf();
+        for depth in 0..=5 {
+            write(&level.join(format!("nivel{}.pdf", depth)), b"%PDF-1.4");
+            level.push(format!("d{}", depth + 1));
+        }
+
+        let options = PdfScanOptions { max_depth: Some(2), ..PdfScanOptions::default() };
+        assert_eq!(names(&scan(dir.path(), options)), ["nivel0.pdf", "nivel1.pdf", "nivel2.pdf"]);
+        assert_eq!(scan(dir.path(), PdfScanOptions::default()).files.len(), 6);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn an_unreadable_folder_is_skipped_not_fatal() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("a.pdf"), b"%PDF-1.4");
+        write(&dir.path().join("z.pdf"), b"%PDF-1.4");
+        let sealed = dir.path().join("sigiloso");
+        write(&sealed.join("oculto.pdf"), b"%PDF-1.4");
+        fs::set_permissions(&sealed, fs::Permissions::from_mode(0o000)).unwrap();
+        // Permission bits don't bind root, so there is nothing to observe there.
+        let enforced = fs::read_dir(&sealed).is_err();
+
+        let listing = scan(dir.path(), PdfScanOptions::default());
+        fs::set_permissions(&sealed, fs::Permissions::from_mode(0o755)).unwrap();
+        if enforced {
+            assert_eq!(names(&listing), ["a.pdf", "z.pdf"]);
+            assert_eq!(listing.skipped.len(), 1);
+            assert_eq!(listing.skipped[0].0, sealed.to_string_lossy());
+        }
+    }
+
+    #[tokio::test]
+    async fn search_matches_names_in_any_case_sorted_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "sub/peticao-final.pdf",
+            "Peticao_Inicial.pdf",
+            "sub/deep/contrato_peticao.pdf",
+            "recurso.pdf",
+            "peticao.txt",
+        ] {
+            write(&dir.path().join(name), b"%PDF-1.4");
+        }
+
+        let folder = dir.path().to_string_lossy().to_string();
+        let found = search_pdfs(folder.clone(), "PETICAO".to_string(), None).await.unwrap();
+        let found: Vec<_> = found.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(found, ["Peticao_Inicial.pdf", "contrato_peticao.pdf", "peticao-final.pdf"]);
+
+        let first = search_pdfs(folder, "peticao".to_string(), Some(1)).await.unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].name, "Peticao_Inicial.pdf");
+    }
+
+    fn folder(name: &str, pdf_count: usize, size: u64, modified: &str) -> ProcessFolder {
+        ProcessFolder {
+            path: format!("/processos/{}", name),
+            name: name.to_string(),
+            root: "/processos".to_string(),
+            pdf_count,
+            total_size_bytes: size,
+            size_display: format_size(size),
+            last_modified: modified.to_string(),
+            latest_modified: modified.to_string(),
+            created: None,
+        }
+    }
+
+    #[test]
+    fn folders_sort_by_each_mode() {
+        let mut folders = vec![
+            folder("b-execucao", 3, 5_000, "2024-03-01T00:00:00Z"),
+            folder("a-inventario", 10, 1_000, "2024-01-01T00:00:00Z"),
+            folder("c-trabalhista", 1, 9_000, "2024-02-01T00:00:00Z"),
+        ];
+        let order = |folders: &[ProcessFolder]| -> Vec<String> {
+            folders.iter().map(|folder| folder.name[..1].to_string()).collect()
+        };
+
+        for (mode, ascending) in [
+            (FolderSortMode::Name, ["a", "b", "c"]),
+            (FolderSortMode::LastModified, ["a", "c", "b"]),
+            (FolderSortMode::PdfCount, ["c", "b", "a"]),
+            (FolderSortMode::TotalSize, ["a", "b", "c"]),
+        ] {
+            sort_folders(&mut folders, mode, false);
+            assert_eq!(order(&folders), ascending, "{:?} ascending", mode);
+            sort_folders(&mut folders, mode, true);
+            let descending: Vec<&str> = ascending.iter().rev().copied().collect();
+            assert_eq!(order(&folders), descending, "{:?} descending", mode);
+        }
+    }
+
+    #[test]
+    fn creation_time_is_reported_where_the_platform_has_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("autos/peticao.pdf");
+        write(&file, b"%PDF-1.4");
+        let expected = fs::metadata(&file).unwrap().created().ok().map(format_time);
+        // Every platform CI runs on records it; elsewhere the field is simply `None`.
+        if cfg!(any(windows, target_os = "macos", target_os = "linux")) {
+            assert!(expected.is_some());
+        }
+
+        assert_eq!(pdf_file_at(&file).unwrap().created, expected);
+        assert_eq!(scan(dir.path(), PdfScanOptions::default()).files[0].created, expected);
+        let mut cancel = CancelCheck::never();
+        let options = ScanOptions::new(PdfScanOptions::default());
+        let folder = build_process_folder(&dir.path().join("autos"), &options, &mut cancel);
+        assert_eq!(folder.unwrap().created.is_some(), expected.is_some());
+    }
+
+    #[tokio::test]
+    async fn duplicates_group_identical_content_only() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("peticao.pdf"), b"%PDF-1.4 peticao");
+        write(&dir.path().join("copia/peticao (final).pdf"), b"%PDF-1.4 peticao");
+        // Same size as the pair, different bytes, so it reaches the hashing step.
+        write(&dir.path().join("recurso.pdf"), b"%PDF-1.4 recurso");
+
+        let folder = dir.path().to_string_lossy().to_string();
+        let duplicates = find_duplicate_pdfs(folder).await.unwrap();
+        let raw = |path: PathBuf| path.to_string_lossy().to_string();
+        let copy = raw(dir.path().join("copia").join("peticao (final).pdf"));
+        assert_eq!(duplicates, [vec![copy, raw(dir.path().join("peticao.pdf"))]]);
+    }
+
+    #[test]
+    fn hidden_folders_are_skipped_when_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("peticao.pdf"), b"%PDF-1.4");
+        write(&dir.path().join(".git/objects/anexo.pdf"), b"%PDF-1.4");
+        write(&dir.path().join("__MACOSX/._peticao.pdf"), b"%PDF-1.4");
+
+        assert_eq!(names(&scan(dir.path(), PdfScanOptions::default())), ["peticao.pdf"]);
+        let everything = PdfScanOptions { skip_hidden: false, ..PdfScanOptions::default() };
+        assert_eq!(scan(dir.path(), everything).files.len(), 3);
+
+        let mut cancel = CancelCheck::never();
+        for (skip_hidden, expected) in [(true, 1), (false, 3)] {
+            let options = ScanOptions::for_folders(&FolderScanOptions {
+                skip_hidden,
+                ..FolderScanOptions::default()
+            });
+            let counted = summarize_dir(dir.path(), &options, &mut cancel).unwrap().pdf_count;
+            assert_eq!(counted, expected, "skip_hidden: {}", skip_hidden);
+        }
+    }
+
+    #[tokio::test]
+    async fn size_breakdown_matches_the_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("grande/a.pdf"), &[0; 3000]);
+        write(&dir.path().join("grande/sub/b.pdf"), &[0; 2000]);
+        write(&dir.path().join("grande/.oculto"), &[0; 500]);
+        write(&dir.path().join("pequena/c.pdf"), &[0; 100]);
+        write(&dir.path().join("vazia/.keep"), &[]);
+        write(&dir.path().join("solto.pdf"), &[0; 700]);
+
+        let folder = dir.path().to_string_lossy().to_string();
+        let breakdown = folder_size_breakdown(folder).await.unwrap();
+        let expected = [("grande", 5500), (".", 700), ("pequena", 100), ("vazia", 0)];
+        let expected: Vec<(String, u64)> =
+            expected.iter().map(|&(name, size)| (name.to_string(), size)).collect();
+        assert_eq!(breakdown, expected);
+    }
+
+    #[test]
+    fn paths_are_made_relative_to_the_given_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("a.pdf"), b"%PDF-1.4");
+        write(&dir.path().join("vol1/anexos/b.pdf"), b"%PDF-1.4");
+        let root = dir.path().to_string_lossy().to_string();
+
+        let options = PdfScanOptions { relative_to: Some(root), ..PdfScanOptions::default() };
+        let listing = scan(dir.path(), options);
+        let paths: Vec<_> = listing.files.iter().map(|file| PathBuf::from(&file.path)).collect();
+        assert_eq!(paths, [PathBuf::from("a.pdf"), Path::new("vol1").join("anexos").join("b.pdf")]);
+        assert!(listing.files.iter().all(|file| Path::new(&file.full_path).is_absolute()));
+
+        // Files outside the prefix keep their absolute path.
+        let elsewhere = Some("/nao/relacionado".to_string());
+        let options = PdfScanOptions { relative_to: elsewhere, ..PdfScanOptions::default() };
+        assert!(scan(dir.path(), options).files.iter().all(|file| file.path == file.full_path));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn an_exclusive_lock_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("aberto.pdf");
+        write(&path, b"%PDF-1.4");
+        let raw = path.to_string_lossy().to_string();
+        assert!(!is_file_locked(raw.clone()).await.unwrap());
+
+        let holder = fs::File::open(&path).unwrap();
+        fs2::FileExt::lock_exclusive(&holder).unwrap();
+        assert!(is_file_locked(raw.clone()).await.unwrap());
+        fs2::FileExt::unlock(&holder).unwrap();
+        assert!(!is_file_locked(raw).await.unwrap());
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn a_file_opened_without_sharing_is_reported() {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("aberto.pdf");
+        write(&path, b"%PDF-1.4");
+        let raw = path.to_string_lossy().to_string();
+        assert!(!is_file_locked(raw.clone()).await.unwrap());
+
+        let holder = fs::OpenOptions::new().read(true).share_mode(0).open(&path).unwrap();
+        assert!(is_file_locked(raw).await.unwrap());
+        assert_eq!(pdf::load_document(&path).unwrap_err().code(), "FILE_LOCKED");
+        drop(holder);
+    }
+
+    #[test]
+    fn natural_sort_orders_numbered_files_by_value() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["doc20.pdf", "doc1.pdf", "doc10.pdf", "doc2.pdf"] {
+            write(&dir.path().join(name), b"%PDF-1.4");
+        }
+
+        let listing = scan(dir.path(), PdfScanOptions::default());
+        assert_eq!(names(&listing), ["doc1.pdf", "doc10.pdf", "doc2.pdf", "doc20.pdf"]);
+        let options = PdfScanOptions { natural_sort: true, ..PdfScanOptions::default() };
+        let listing = scan(dir.path(), options);
+        assert_eq!(names(&listing), ["doc1.pdf", "doc2.pdf", "doc10.pdf", "doc20.pdf"]);
+    }
+
+    // macOS refuses file names that aren't valid UTF-8, so there is nothing to list.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[tokio::test]
+    async fn a_non_utf8_name_round_trips_through_raw_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let sample = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/peticao.pdf");
+        // "peticao" in Latin-1, as left behind by older imports.
+        let path = dir.path().join(OsStr::from_bytes(b"peti\xe7\xe3o.pdf"));
+        fs::copy(sample, &path).unwrap();
+
+        let listing = scan(dir.path(), PdfScanOptions::default());
+        let file = &listing.files[0];
+        assert!(file.name.contains(char::REPLACEMENT_CHARACTER), "{:?}", file.name);
+        assert_eq!(file.raw_path.as_deref(), Some(path.as_os_str().as_bytes()));
+
+        let lossy = pdf::extract_pdf_text(file.full_path.clone(), None, None, None).await;
+        assert_eq!(lossy.unwrap_err().code(), "INVALID_PATH_ENCODING");
+        let raw = file.raw_path.clone();
+        let text = pdf::extract_pdf_text(file.full_path.clone(), raw, None, None).await.unwrap();
+        assert!(text.contains("Excelentissimo"), "{:?}", text);
+    }
+
+    #[test]
+    fn only_folders_changed_after_the_cutoff_are_listed() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("antigo/peticao.pdf"), b"%PDF-1.4");
+        let late = dir.path().join("novo/recursos/apelacao.pdf");
+        write(&late, b"%PDF-1.4");
+        write(&dir.path().join("novo/peticao.pdf"), b"%PDF-1.4");
+        // Everything on disk predates the cutoff except the nested file, so
+        // "novo" only qualifies through its deepest mtime.
+        let now = std::time::SystemTime::now();
+        let cutoff = DateTime::<Utc>::from(now + Duration::from_secs(3600));
+        let file = fs::File::options().write(true).open(&late).unwrap();
+        file.set_modified(now + Duration::from_secs(7200)).unwrap();
+
+        let mut cancel = CancelCheck::never();
+        let options = ScanOptions::for_folders(&FolderScanOptions::default());
+        let folders: Vec<ProcessFolder> = ["antigo", "novo"]
+            .iter()
+            .map(|name| build_process_folder(&dir.path().join(name), &options, &mut cancel))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let changed = modified_after(folders, cutoff);
+        let names: Vec<_> = changed.iter().map(|folder| folder.name.as_str()).collect();
+        assert_eq!(names, ["novo"]);
+    }
+
+    #[test]
+    fn the_csv_listing_has_a_header_and_one_escaped_row_per_pdf() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("autos");
+        let pdf = folder.join("peticao, final.pdf");
+        fs::create_dir(&folder).unwrap();
+        fs::copy(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/peticao.pdf"), &pdf).unwrap();
+        let output = dir.path().join("lista.csv");
+
+        let registry = ExtractionRegistry::default();
+        let rows = write_folder_listing(&folder, &output, &registry, &HashSet::new()).unwrap();
+        assert_eq!(rows, 1);
+        let csv = fs::read_to_string(&output).unwrap();
+        let mut lines = csv.lines();
+        let header = "name,path,size_bytes,last_modified,page_count,extraction_status";
+        assert_eq!(lines.next(), Some(header));
+        assert!(lines.next().unwrap().starts_with("\"peticao, final.pdf\","), "{}", csv);
+        assert_eq!(lines.next(), None);
+
+        let file = pdf_file_at(&pdf).unwrap();
+        let mut reader = csv::Reader::from_path(&output).unwrap();
+        let row = reader.records().next().unwrap().unwrap();
+        let size = file.size_bytes.to_string();
+        let expected = [
+            "peticao, final.pdf", &file.full_path, &size, &file.last_modified, "1", "Pending",
+        ];
+        assert_eq!(row.iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn verifying_magic_lists_by_content_not_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("peticao.pdf"), b"%PDF-1.4 peticao");
+        write(&dir.path().join("digitalizado.txt"), b"%PDF-1.7 salvo com a extensao errada");
+        write(&dir.path().join("anotacoes.pdf"), b"apenas texto renomeado");
+
+        let listing = scan(dir.path(), PdfScanOptions::default());
+        assert_eq!(names(&listing), ["anotacoes.pdf", "peticao.pdf"]);
+        assert!(listing.files.iter().all(|file| file.is_real_pdf.is_none()));
+
+        let options = PdfScanOptions { verify_magic: true, ..PdfScanOptions::default() };
+        let listing = scan(dir.path(), options);
+        assert_eq!(names(&listing), ["digitalizado.txt", "peticao.pdf"]);
+        assert!(listing.files.iter().all(|file| file.is_real_pdf == Some(true)));
+    }
+
+    #[test]
+    fn folders_of_valid_roots_are_merged_and_the_invalid_one_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("cliente-a");
+        let second = dir.path().join("cliente-b");
+        write(&first.join("processo-1/peticao.pdf"), b"%PDF-1.4");
+        write(&first.join("processo-2/sentenca.pdf"), b"%PDF-1.4");
+        write(&second.join("processo-3/recurso.pdf"), b"%PDF-1.4");
+        let raw = |path: &Path| path.to_string_lossy().to_string();
+        let missing = raw(&dir.path().join("desconectado"));
+
+        let registry = ScanRegistry::default();
+        let updates = AtomicUsize::new(0);
+        let progress = ProgressEmitter::with_sink(|_| {
+            updates.fetch_add(1, Ordering::Relaxed);
+        });
+        let roots = vec![raw(&first), missing.clone(), raw(&second)];
+        let options = FolderScanOptions::default();
+        let listing = scan_roots(roots, &registry, None, &options, &progress).unwrap();
+
+        let mut folders: Vec<_> =
+            listing.folders.iter().map(|f| (f.name.as_str(), f.root.clone())).collect();
+        folders.sort();
+        assert_eq!(folders, [
+            ("processo-1", raw(&first)),
+            ("processo-2", raw(&first)),
+            ("processo-3", raw(&second)),
+        ]);
+        assert_eq!(listing.skipped.len(), 1);
+        assert_eq!(listing.skipped[0].0, missing);
+        assert!(updates.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn the_csv_reports_text_cached_in_an_earlier_session_as_completed() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("autos/extraido.pdf"), b"%PDF-1.4 extraido");
+        write(&dir.path().join("autos/novo.pdf"), b"%PDF-1.4 novo");
+        let extracted_pdf = dir.path().join("autos").join("extraido.pdf");
+        let hash = cache::hash_path(&extracted_pdf, HashAlgorithm::Sha256).unwrap();
+        let output = dir.path().join("lista.csv");
+
+        let registry = ExtractionRegistry::default();
+        let extracted = HashSet::from([hash]);
+        write_folder_listing(&dir.path().join("autos"), &output, &registry, &extracted).unwrap();
+        let mut reader = csv::Reader::from_path(&output).unwrap();
+        let statuses: Vec<(String, String)> = reader
+            .records()
+            .map(|row| {
+                let row = row.unwrap();
+                (row[0].to_string(), row[5].to_string())
+            })
+            .collect();
+        assert_eq!(statuses, [
+            ("extraido.pdf".to_string(), "Completed".to_string()),
+            ("novo.pdf".to_string(), "Pending".to_string()),
+        ]);
+    }
+}
@@ -1,2 +1,8 @@
 pub mod filesystem;
 pub mod cache;
+pub mod pdf;
+pub mod extraction;
+pub mod watcher;
+pub mod backend;
+pub mod ocr;
+pub mod thumbnail;
@@ -0,0 +1,72 @@
+use crate::models::AppError;
+use std::path::Path;
+
+/// Width in pixels pages are rendered at before OCR, roughly A4 at 300 DPI.
+#[cfg(feature = "ocr")]
+const OCR_RENDER_WIDTH: i32 = 2480;
+
+#[cfg(feature = "ocr")]
+const OCR_SOURCE_DPI: i32 = 300;
+
+/// Renders every page and runs Tesseract over it. `lang` takes Tesseract
+/// language codes such as `por`, `eng` or `por+eng`; anything else is an
+/// `InvalidArgument`. Builds without the `ocr` feature return
+/// `ExtractionFailed` explaining that OCR is unavailable.
+#[tauri::command]
+pub async fn ocr_pdf(
+    app: tauri::AppHandle,
+    file_path: String,
+    lang: String,
+) -> Result<String, AppError> {
+    let path = Path::new(&file_path);
+    if !path.is_file() {
+        return Err(AppError::FileNotFound(file_path));
+    }
+    if lang.is_empty() || !lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '_') {
+        return Err(AppError::InvalidArgument(format!("idioma do OCR invalido: {:?}", lang)));
+    }
+
+    run_ocr(&app, path, &lang)
+}
+
+#[cfg(feature = "ocr")]
+fn run_ocr(app: &tauri::AppHandle, path: &Path, lang: &str) -> Result<String, AppError> {
+    use crate::commands::pdf;
+    use pdfium_render::prelude::PdfRenderConfig;
+
+    let mut tesseract = leptess::LepTess::new(None, lang).map_err(|e| {
+        AppError::ExtractionFailed(format!(
+            "Tesseract ou dados do idioma '{}' nao encontrados: {}",
+            lang, e
+        ))
+    })?;
+
+    let pdfium = pdf::bind_pdfium(app)?;
+    let document = pdfium
+        .load_pdf_from_file(path, None)
+        .map_err(|e| AppError::ExtractionFailed(format!("{}: {}", path.display(), e)))?;
+    let config = PdfRenderConfig::new().set_target_width(OCR_RENDER_WIDTH);
+
+    let mut pages = Vec::new();
+    for page in document.pages().iter() {
+        let png = pdf::render_png(&page, &config)?;
+        tesseract
+            .set_image_from_mem(&png)
+            .map_err(|e| AppError::ExtractionFailed(e.to_string()))?;
+        tesseract.set_source_resolution(OCR_SOURCE_DPI);
+        pages.push(
+            tesseract
+                .get_utf8_text()
+                .map_err(|e| AppError::ExtractionFailed(e.to_string()))?,
+        );
+    }
+
+    Ok(pages.join("\n"))
+}
+
+#[cfg(not(feature = "ocr"))]
+fn run_ocr(_app: &tauri::AppHandle, _path: &Path, _lang: &str) -> Result<String, AppError> {
+    Err(AppError::ExtractionFailed(
+        "OCR indisponivel: build sem a feature `ocr` (Tesseract)".to_string(),
+    ))
+}
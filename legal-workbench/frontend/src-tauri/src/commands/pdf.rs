@@ -0,0 +1,1572 @@
+use crate::commands::cache;
+use crate::commands::filesystem::{self, format_time};
+use crate::models::{
+    AppError, HashAlgorithm, LanguageDetection, PageCountSummary, PathContext, PdfFile, PdfMetadata,
+    PdfValidation, TextChunk, WordPosition,
+};
+use chrono::{FixedOffset, NaiveDate, TimeZone};
+use lopdf::encryption::DecryptionError;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use pdfium_render::prelude::{PdfPage, PdfRenderConfig, Pdfium};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+/// Pass `raw_path` from the listing for files whose name isn't valid UTF-8.
+/// With `timeout_seconds`, gives up with `ExtractionFailed("timeout")` once it
+/// elapses; the parser thread can't be interrupted and finishes in the background.
+/// With `clean`, the text goes through `clean_extracted_text` before returning.
+#[tauri::command]
+pub async fn extract_pdf_text(
+    file_path: String,
+    raw_path: Option<Vec<u8>>,
+    timeout_seconds: Option<u64>,
+    clean: Option<bool>,
+) -> Result<String, AppError> {
+    let path = filesystem::resolve_path(&file_path, raw_path)?;
+    let text = match timeout_seconds {
+        None => extract_text(&path)?,
+        Some(seconds) => {
+            let task = tokio::task::spawn_blocking(move || extract_text(&path));
+            match tokio::time::timeout(Duration::from_secs(seconds), task).await {
+                Ok(Ok(result)) => result?,
+                Ok(Err(e)) => return Err(AppError::ExtractionFailed(e.to_string())),
+                Err(_) => return Err(AppError::ExtractionFailed("timeout".to_string())),
+            }
+        }
+    };
+
+    Ok(if clean.unwrap_or(false) { clean_text(&text) } else { text })
+}
+
+/// Tidies raw extracted text for analysis: joins words hyphenated across line
+/// breaks, expands ligatures (`ﬁ` → `fi`), drops control characters and
+/// collapses whitespace. Blank lines between paragraphs are kept as one.
+#[tauri::command]
+pub async fn clean_extracted_text(text: String) -> String {
+    clean_text(&text)
+}
+
+/// First `max_chars` characters of the document's text. Pages are extracted
+/// one at a time and extraction stops as soon as enough text has been read,
+/// so previews of long documents stay cheap.
+#[tauri::command]
+pub async fn extract_text_preview(file_path: String, max_chars: usize) -> Result<String, AppError> {
+    let doc = load_document(Path::new(&file_path))?;
+    Ok(text_prefix(&doc, max_chars))
+}
+
+/// Characters of text sampled for language detection; plenty for whatlang.
+const LANGUAGE_SAMPLE_CHARS: usize = 4000;
+
+/// Detects the dominant language from a sample of the document's text, to
+/// route it to the right OCR or analysis pipeline. Scanned PDFs come back
+/// with `has_text: false` and no language instead of a guess.
+#[tauri::command]
+pub async fn detect_pdf_language(file_path: String) -> Result<LanguageDetection, AppError> {
+    let doc = load_document(Path::new(&file_path))?;
+    let sample = text_prefix(&doc, LANGUAGE_SAMPLE_CHARS);
+    if sample.trim().is_empty() {
+        return Ok(LanguageDetection { language: None, confidence: 0.0, has_text: false });
+    }
+
+    let detection = match whatlang::detect(&sample) {
+        Some(info) => LanguageDetection {
+            language: Some(info.lang().code().to_string()),
+            confidence: info.confidence(),
+            has_text: true,
+        },
+        None => LanguageDetection { language: None, confidence: 0.0, has_text: true },
+    };
+    Ok(detection)
+}
+
+/// Words on `page` (1-based) with their bounding boxes, for highlighting
+/// search hits in the viewer. Boxes use a top-left origin to match the
+/// frontend's coordinate system. Needs PDFium, like thumbnails.
+#[tauri::command]
+pub async fn extract_pdf_text_positions(
+    app: tauri::AppHandle,
+    file_path: String,
+    page: usize,
+) -> Result<Vec<WordPosition>, AppError> {
+    let pdfium = bind_pdfium(&app)?;
+    word_positions(&pdfium, Path::new(&file_path), page)
+}
+
+fn word_positions(
+    pdfium: &Pdfium,
+    path: &Path,
+    page: usize,
+) -> Result<Vec<WordPosition>, AppError> {
+    let document = pdfium
+        .load_pdf_from_file(path, None)
+        .map_err(|e| AppError::ExtractionFailed(format!("{}: {}", path.display(), e)))?;
+
+    let page_count = document.pages().len() as usize;
+    if page == 0 || page > page_count {
+        return Err(AppError::InvalidArgument(format!(
+            "pagina {} fora do documento ({} paginas)",
+            page, page_count
+        )));
+    }
+    let pdf_page = document
+        .pages()
+        .get((page - 1) as u16)
+        .map_err(|e| AppError::ExtractionFailed(e.to_string()))?;
+    let page_height = pdf_page.height().value;
+    let text = pdf_page
+        .text()
+        .map_err(|e| AppError::ExtractionFailed(e.to_string()))?;
+
+    let mut words = Vec::new();
+    let mut current: Option<(String, f32, f32, f32, f32)> = None;
+    for ch in text.chars().iter() {
+        let glyph = ch.unicode_char();
+        if glyph.is_none_or(char::is_whitespace) {
+            words.extend(current.take().map(|word| to_word_position(word, page_height)));
+            continue;
+        }
+        let Ok(bounds) = ch.loose_bounds() else {
+            continue;
+        };
+        let (left, bottom, right, top) =
+            (bounds.left().value, bounds.bottom().value, bounds.right().value, bounds.top().value);
+        match current.as_mut() {
+            Some((word, l, b, r, t)) => {
+                word.extend(glyph);
+                *l = l.min(left);
+                *b = b.min(bottom);
+                *r = r.max(right);
+                *t = t.max(top);
+            }
+            None => current = Some((glyph.into_iter().collect(), left, bottom, right, top)),
+        }
+    }
+    words.extend(current.map(|word| to_word_position(word, page_height)));
+    Ok(words)
+}
+
+/// Converts a word's PDF-space box (bottom-left origin) to a top-left origin.
+fn to_word_position(
+    (text, left, bottom, right, top): (String, f32, f32, f32, f32),
+    page_height: f32,
+) -> WordPosition {
+    WordPosition {
+        text,
+        x: left,
+        y: page_height - top,
+        width: right - left,
+        height: top - bottom,
+    }
+}
+
+#[tauri::command]
+pub async fn extract_pdf_metadata(file_path: String) -> Result<PdfMetadata, AppError> {
+    let doc = load_document(Path::new(&file_path))?;
+
+    // Missing or malformed Info dictionaries just yield an all-`None` result.
+    let info = doc
+        .trailer
+        .get(b"Info")
+        .ok()
+        .and_then(|obj| match obj {
+            Object::Reference(id) => doc.get_object(*id).ok(),
+            other => Some(other),
+        })
+        .and_then(|obj| obj.as_dict().ok());
+
+    let field = |key: &[u8]| info.and_then(|dict| info_string(dict, key));
+    Ok(PdfMetadata {
+        title: field(b"Title"),
+        author: field(b"Author"),
+        subject: field(b"Subject"),
+        creation_date: field(b"CreationDate").and_then(|raw| parse_pdf_date(&raw)),
+        producer: field(b"Producer"),
+    })
+}
+
+/// Text of each page; index `i` holds page `i + 1`. Pages without extractable
+/// text come back as empty strings so indices stay aligned with page numbers.
+#[tauri::command]
+pub async fn extract_pdf_pages(file_path: String) -> Result<Vec<String>, AppError> {
+    let doc = load_document(Path::new(&file_path))?;
+    let pages = doc
+        .get_pages()
+        .keys()
+        .map(|&page_number| doc.extract_text(&[page_number]).unwrap_or_default())
+        .collect();
+    Ok(pages)
+}
+
+/// Extracts `page_batch_size` pages at a time and emits each batch as a
+/// `text-chunk` event, so only one batch of text is held in memory however
+/// long the document is. Returns the number of pages processed.
+#[tauri::command]
+pub async fn extract_pdf_text_chunked(
+    app: tauri::AppHandle,
+    file_path: String,
+    page_batch_size: usize,
+) -> Result<usize, AppError> {
+    if page_batch_size == 0 {
+        return Err(AppError::InvalidArgument(
+            "page_batch_size deve ser maior que zero".to_string(),
+        ));
+    }
+    let doc = load_document(Path::new(&file_path))?;
+    extract_in_chunks(&doc, &file_path, page_batch_size, |chunk| {
+        let _ = app.emit("text-chunk", chunk);
+    })
+}
+
+/// Hands each batch of `page_batch_size` pages to `emit` as soon as it is
+/// extracted. Returns the number of pages processed.
+fn extract_in_chunks(
+    doc: &Document,
+    file_path: &str,
+    page_batch_size: usize,
+    mut emit: impl FnMut(TextChunk),
+) -> Result<usize, AppError> {
+    let page_numbers: Vec<u32> = doc.get_pages().keys().copied().collect();
+
+    for (batch, pages) in page_numbers.chunks(page_batch_size).enumerate() {
+        let start_page = batch * page_batch_size + 1;
+        let text = doc
+            .extract_text(pages)
+            .map_err(|e| AppError::ExtractionFailed(format!("{}: {}", file_path, e)))?;
+        emit(TextChunk {
+            path: file_path.to_string(),
+            start_page,
+            end_page: start_page + pages.len() - 1,
+            text,
+        });
+    }
+    Ok(page_numbers.len())
+}
+
+/// Concatenates `input_paths` in order into a new PDF at `output_path`.
+/// Every input is validated before anything is written, and an existing
+/// output file is never overwritten.
+#[tauri::command]
+pub async fn merge_pdfs(input_paths: Vec<String>, output_path: String) -> Result<PdfFile, AppError> {
+    if input_paths.is_empty() {
+        return Err(AppError::InvalidArgument("nenhum PDF para juntar".to_string()));
+    }
+    let output = Path::new(&output_path);
+    if output.exists() {
+        return Err(AppError::AlreadyExists(output_path));
+    }
+
+    let mut documents = Vec::with_capacity(input_paths.len());
+    for input in &input_paths {
+        let path = Path::new(input);
+        if filesystem::matching_extension(path, filesystem::PDF_ONLY).is_none() {
+            return Err(AppError::InvalidFile(input.clone()));
+        }
+        let doc = load_document(path)?;
+        if doc.is_encrypted() {
+            return Err(AppError::InvalidFile("PDF protegido por senha".to_string()).at_path(path));
+        }
+        documents.push(doc);
+    }
+
+    let mut merged = merge_documents(documents)?;
+    save_new(&mut merged, output)?;
+    filesystem::pdf_file_at(output)
+}
+
+/// Saves `doc` to `path`, which must not exist yet. The file is claimed with
+/// `create_new`, so one that appeared after an earlier check fails with
+/// `AlreadyExists` instead of being truncated; a partial file is removed.
+fn save_new(doc: &mut Document, path: &Path) -> Result<(), AppError> {
+    let file = match std::fs::OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            return Err(AppError::AlreadyExists(path.to_string_lossy().to_string()));
+        }
+        Err(e) => return Err(e).at_path(path),
+    };
+    let mut writer = std::io::BufWriter::new(file);
+    let saved = doc
+        .save_to(&mut writer)
+        .map_err(|e| e.to_string())
+        .and_then(|_| std::io::Write::flush(&mut writer).map_err(|e| e.to_string()));
+    if let Err(e) = saved {
+        drop(writer);
+        let _ = std::fs::remove_file(path);
+        return Err(AppError::IoError(format!("{}: {}", path.display(), e)));
+    }
+    Ok(())
+}
+
+/// Writes each inclusive, 1-based page range of `file_path` to its own PDF in
+/// `output_dir`, named `<stem>_p<start>-<end>.pdf`. Ranges must lie within the
+/// document and may not overlap; they need not be sorted or cover every page.
+#[tauri::command]
+pub async fn split_pdf(
+    file_path: String,
+    ranges: Vec<(usize, usize)>,
+    output_dir: String,
+) -> Result<Vec<PdfFile>, AppError> {
+    let path = Path::new(&file_path);
+    let out_dir = Path::new(&output_dir);
+    if !out_dir.is_dir() {
+        return Err(AppError::InvalidDirectory(output_dir));
+    }
+
+    let doc = load_document(path)?;
+    if doc.is_encrypted() {
+        return Err(AppError::InvalidFile("PDF protegido por senha".to_string()).at_path(path));
+    }
+    let pages = doc.get_pages();
+    validate_ranges(&ranges, pages.len())?;
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "documento".to_string());
+    let outputs: Vec<_> = ranges
+        .iter()
+        .map(|&(start, end)| out_dir.join(format!("{}_p{}-{}.pdf", stem, start, end)))
+        .collect();
+    if let Some(existing) = outputs.iter().find(|output| output.exists()) {
+        return Err(AppError::AlreadyExists(existing.to_string_lossy().to_string()));
+    }
+
+    let mut created = Vec::with_capacity(ranges.len());
+    for (&(start, end), output) in ranges.iter().zip(&outputs) {
+        let mut part = doc.clone();
+        let dropped: Vec<u32> = pages
+            .keys()
+            .copied()
+            .filter(|&number| (number as usize) < start || (number as usize) > end)
+            .collect();
+        part.delete_pages(&dropped);
+        part.prune_objects();
+        part.compress();
+        part.save(output)
+            .map_err(|e| AppError::IoError(format!("{}: {}", output.display(), e)))?;
+        created.push(filesystem::pdf_file_at(output)?);
+    }
+    Ok(created)
+}
+
+/// Ranges are 1-based and inclusive; each must be non-empty, within
+/// `page_count`, and disjoint from the others.
+fn validate_ranges(ranges: &[(usize, usize)], page_count: usize) -> Result<(), AppError> {
+    if ranges.is_empty() {
+        return Err(AppError::InvalidArgument("nenhum intervalo de paginas".to_string()));
+    }
+    for &(start, end) in ranges {
+        if start == 0 || start > end || end > page_count {
+            return Err(AppError::InvalidArgument(format!(
+                "intervalo {}-{} fora do documento ({} paginas)",
+                start, end, page_count
+            )));
+        }
+    }
+
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable();
+    if let Some(pair) = sorted.windows(2).find(|pair| pair[1].0 <= pair[0].1) {
+        return Err(AppError::InvalidArgument(format!(
+            "intervalos {}-{} e {}-{} se sobrepoem",
+            pair[0].0, pair[0].1, pair[1].0, pair[1].1
+        )));
+    }
+    Ok(())
+}
+
+/// Sums the page counts of every PDF under `folder_path`, parsing files in
+/// parallel. Only the page tree is read; no text is extracted.
+#[tauri::command]
+pub async fn total_page_count(folder_path: String) -> Result<PageCountSummary, AppError> {
+    let path = Path::new(&folder_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+
+    let counts: Vec<(String, Option<usize>)> = filesystem::find_pdfs(path)?
+        .into_par_iter()
+        .map(|pdf| {
+            let pages = page_count(Path::new(&pdf.path));
+            (pdf.path, pages)
+        })
+        .collect();
+
+    let mut summary = PageCountSummary {
+        total_pages: 0,
+        file_count: counts.len(),
+        corrupt: Vec::new(),
+    };
+    for (path, pages) in counts {
+        match pages {
+            Some(pages) => summary.total_pages += pages,
+            None => summary.corrupt.push(path),
+        }
+    }
+    Ok(summary)
+}
+
+/// Bytes inspected at each end of the file for the header and trailer markers.
+const VALIDATION_WINDOW: u64 = 1024;
+
+/// Checks the header, trailer markers and that the xref table and page tree
+/// parse, without extracting any text.
+#[tauri::command]
+pub async fn validate_pdf(file_path: String) -> Result<PdfValidation, AppError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let path = Path::new(&file_path);
+    let mut file = std::fs::File::open(path).at_path(path)?;
+    let len = file.metadata().at_path(path)?.len();
+    let mut warnings = Vec::new();
+
+    let mut head = Vec::new();
+    (&mut file).take(VALIDATION_WINDOW).read_to_end(&mut head).at_path(path)?;
+    let has_header = head.starts_with(b"%PDF-");
+    if !has_header {
+        warnings.push("cabecalho %PDF- ausente".to_string());
+    }
+
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(len.saturating_sub(VALIDATION_WINDOW))).at_path(path)?;
+    file.read_to_end(&mut tail).at_path(path)?;
+    let contains = |haystack: &[u8], needle: &[u8]| haystack.windows(needle.len()).any(|w| w == needle);
+    if !contains(&tail, b"%%EOF") {
+        warnings.push("marcador %%EOF ausente (arquivo truncado?)".to_string());
+    }
+    if !contains(&tail, b"startxref") {
+        warnings.push("startxref ausente".to_string());
+    }
+
+    file.seek(SeekFrom::Start(0)).at_path(path)?;
+    let (parsed, encrypted, page_count) = match Document::load_from(file) {
+        Ok(doc) => {
+            let pages = doc.get_pages().len();
+            if pages == 0 {
+                warnings.push("arvore de paginas vazia".to_string());
+            }
+            if doc.trailer.get(b"Root").is_err() {
+                warnings.push("trailer sem /Root".to_string());
+            }
+            let encrypted = doc.is_encrypted() || doc.trailer.has(b"Encrypt");
+            (pages > 0, encrypted, Some(pages))
+        }
+        Err(e) => {
+            warnings.push(format!("estrutura invalida: {}", e));
+            (false, contains(&tail, b"/Encrypt"), None)
+        }
+    };
+
+    Ok(PdfValidation {
+        is_valid: has_header && parsed,
+        encrypted,
+        page_count,
+        warnings,
+    })
+}
+
+/// Opens a password-protected PDF with `password` and writes an unprotected
+/// copy to `output_path`. Fails with `NotEncrypted` for unprotected input,
+/// `WrongPassword` only when the password is rejected. Encryption lopdf can't
+/// undo (anything but RC4 revisions 2-3, e.g. AES) fails with
+/// `ExtractionFailed` whatever the password.
+#[tauri::command]
+pub async fn decrypt_pdf(
+    input_path: String,
+    password: String,
+    output_path: String,
+) -> Result<PdfFile, AppError> {
+    let output = Path::new(&output_path);
+    if output.exists() {
+        return Err(AppError::AlreadyExists(output_path));
+    }
+
+    let mut doc = load_document(Path::new(&input_path))?;
+    if !doc.is_encrypted() {
+        return Err(AppError::NotEncrypted(input_path));
+    }
+    doc.decrypt(&password).map_err(|e| match e {
+        lopdf::Error::Decryption(DecryptionError::IncorrectPassword) => {
+            AppError::WrongPassword(input_path.clone())
+        }
+        other => AppError::ExtractionFailed(format!("criptografia nao suportada ({})", other))
+            .at_path(Path::new(&input_path)),
+    })?;
+    doc.trailer.remove(b"Encrypt");
+
+    save_new(&mut doc, output)?;
+    filesystem::pdf_file_at(output)
+}
+
+/// Words per shingle in `pdf_text_similarity`.
+const SHINGLE_WORDS: usize = 3;
+
+/// Jaccard similarity (0.0 to 1.0) of the two documents' word 3-shingles,
+/// for spotting near-duplicates that aren't byte-identical. Case and
+/// punctuation are ignored. Text comes from the extracted-text cache when
+/// present and is stored there otherwise. A PDF without text, typically a
+/// scan, fails with `ExtractionFailed` rather than scoring 0.
+#[tauri::command]
+pub async fn pdf_text_similarity(
+    app: tauri::AppHandle,
+    path_a: String,
+    path_b: String,
+) -> Result<f32, AppError> {
+    let text_a = cached_text(&app, Path::new(&path_a))?;
+    let text_b = cached_text(&app, Path::new(&path_b))?;
+    text_similarity((&path_a, &text_a), (&path_b, &text_b))
+}
+
+/// Jaccard similarity of two `(path, text)` pairs; the paths only name an
+/// empty text in the error.
+fn text_similarity(a: (&str, &str), b: (&str, &str)) -> Result<f32, AppError> {
+    let shingles_a = shingles(a.1);
+    let shingles_b = shingles(b.1);
+    for (path, set) in [(a.0, &shingles_a), (b.0, &shingles_b)] {
+        if set.is_empty() {
+            return Err(AppError::ExtractionFailed(format!(
+                "{}: PDF sem texto, OCR necessario",
+                path
+            )));
+        }
+    }
+
+    let shared = shingles_a.intersection(&shingles_b).count();
+    let total = shingles_a.len() + shingles_b.len() - shared;
+    Ok(shared as f32 / total as f32)
+}
+
+/// The file's text from the extracted-text cache, extracting and storing it on
+/// a miss. Cache errors fall back to extracting.
+fn cached_text(app: &tauri::AppHandle, path: &Path) -> Result<String, AppError> {
+    let file_hash = cache::hash_path(path, HashAlgorithm::Sha256)?;
+    if let Ok(Some(text)) = cache::load_extracted_text(app, &file_hash) {
+        return Ok(text);
+    }
+    let text = extract_text(path)?;
+    let _ = cache::store_extracted_text(app, &file_hash, &text);
+    Ok(text)
+}
+
+/// Overlapping runs of `SHINGLE_WORDS` lowercase words; a shorter text is a
+/// single shingle.
+fn shingles(text: &str) -> HashSet<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return HashSet::new();
+    }
+    words.windows(SHINGLE_WORDS.min(words.len())).map(|window| window.join(" ")).collect()
+}
+
+/// Writes every file embedded through the document's `EmbeddedFiles` name
+/// tree into `output_dir` and returns their metadata. A PDF without
+/// attachments yields an empty list. Existing files are never overwritten:
+/// a taken name, on disk or by an earlier attachment, gets a ` (1)`, ` (2)`, …
+/// suffix. Names are settled before anything is written, and a failed write
+/// removes what this call already wrote.
+#[tauri::command]
+pub async fn extract_pdf_attachments(
+    file_path: String,
+    output_dir: String,
+) -> Result<Vec<PdfFile>, AppError> {
+    let out_dir = Path::new(&output_dir);
+    if !out_dir.is_dir() {
+        return Err(AppError::InvalidDirectory(output_dir));
+    }
+    let doc = load_document(Path::new(&file_path))?;
+
+    let mut attachments = Vec::new();
+    if let Some(tree) = embedded_files_tree(&doc) {
+        collect_file_specs(&doc, tree, &mut attachments, 0);
+    }
+
+    let mut outputs: Vec<PathBuf> = Vec::with_capacity(attachments.len());
+    for (index, (name, _)) in attachments.iter().enumerate() {
+        // Only the final component, so a crafted name can't escape `output_dir`.
+        let file_name = Path::new(name)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| format!("anexo_{}", index + 1));
+        let output = filesystem::candidate_names(out_dir, Path::new(&file_name))?
+            .find(|candidate| !candidate.exists() && !outputs.contains(candidate))
+            .ok_or_else(|| {
+                AppError::AlreadyExists(out_dir.join(&file_name).to_string_lossy().to_string())
+            })?;
+        outputs.push(output);
+    }
+
+    for (index, ((_, content), output)) in attachments.iter().zip(&outputs).enumerate() {
+        if let Err(e) = write_new_file(output, content) {
+            for done in &outputs[..index] {
+                let _ = std::fs::remove_file(done);
+            }
+            return Err(e);
+        }
+    }
+    outputs.iter().map(|output| filesystem::pdf_file_at(output)).collect()
+}
+
+/// Writes `content` to a file that must not exist yet; a partial file is removed.
+fn write_new_file(path: &Path, content: &[u8]) -> Result<(), AppError> {
+    use std::io::Write;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .at_path(path)?;
+    if let Err(e) = file.write_all(content) {
+        drop(file);
+        let _ = std::fs::remove_file(path);
+        return Err(e).at_path(path);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn is_scanned_pdf(file_path: String) -> Result<bool, AppError> {
+    let doc = load_document(Path::new(&file_path))?;
+    Ok(is_scanned(&doc))
+}
+
+/// Concatenated text of every page, in page order.
+pub(crate) fn extract_text(path: &Path) -> Result<String, AppError> {
+    let doc = load_document(path)?;
+    document_text(&doc, path)
+}
+
+pub(crate) fn document_text(doc: &Document, path: &Path) -> Result<String, AppError> {
+    let pages: Vec<u32> = doc.get_pages().keys().copied().collect();
+    doc.extract_text(&pages)
+        .map_err(|e| AppError::ExtractionFailed(format!("{}: {}", path.display(), e)))
+}
+
+/// Presentation-form ligatures some PDF producers emit instead of letter pairs.
+const LIGATURES: &[(char, &str)] = &[
+    ('\u{FB00}', "ff"),
+    ('\u{FB01}', "fi"),
+    ('\u{FB02}', "fl"),
+    ('\u{FB03}', "ffi"),
+    ('\u{FB04}', "ffl"),
+    ('\u{FB05}', "st"),
+    ('\u{FB06}', "st"),
+];
+
+fn clean_text(text: &str) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    for ch in text.replace("\r\n", "\n").chars() {
+        match LIGATURES.iter().find(|(ligature, _)| *ligature == ch) {
+            Some((_, letters)) => expanded.push_str(letters),
+            // Soft hyphens only mark where a word may break; they aren't text.
+            None if ch == '\u{AD}' => {}
+            None if ch == '\r' || ch == '\n' => expanded.push('\n'),
+            None if ch.is_control() && ch != '\t' => {}
+            None => expanded.push(ch),
+        }
+    }
+
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    for line in expanded.split('\n') {
+        let words = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if words.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        // "proces-" at the end of a line followed by "so" joins into "processo";
+        // a capitalized continuation ("Rio-\nGrande") keeps its hyphen.
+        let hyphenated = current
+            .strip_suffix('-')
+            .is_some_and(|rest| rest.chars().last().is_some_and(char::is_alphabetic));
+        let continues_word = words.chars().next().is_some_and(char::is_lowercase);
+        if hyphenated && continues_word {
+            current.pop();
+        } else if !current.is_empty() && !hyphenated {
+            current.push(' ');
+        }
+        current.push_str(&words);
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs.join("\n\n")
+}
+
+/// Up to `max_chars` characters from the start of the document, extracting
+/// pages only until enough text has been read.
+fn text_prefix(doc: &Document, max_chars: usize) -> String {
+    let pages = doc.get_pages().into_keys();
+    read_prefix(pages, max_chars, |page| doc.extract_text(&[page]).unwrap_or_default())
+}
+
+/// Reads pages through `page_text` in order until `max_chars` characters are
+/// in, then cuts at that character.
+fn read_prefix(
+    pages: impl IntoIterator<Item = u32>,
+    max_chars: usize,
+    mut page_text: impl FnMut(u32) -> String,
+) -> String {
+    let mut prefix = String::new();
+    let mut char_count = 0;
+
+    for page_number in pages {
+        if char_count >= max_chars {
+            break;
+        }
+        let text = page_text(page_number);
+        char_count += text.chars().count();
+        prefix.push_str(&text);
+    }
+
+    prefix.chars().take(max_chars).collect()
+}
+
+/// True when no page shows text but at least one paints an XObject (`Do`),
+/// i.e. the document is page images that need OCR.
+pub(crate) fn is_scanned(doc: &Document) -> bool {
+    let mut paints_xobjects = false;
+    for page_id in doc.get_pages().into_values() {
+        let Ok(content) = doc.get_and_decode_page_content(page_id) else {
+            continue;
+        };
+        for op in &content.operations {
+            match op.operator.as_str() {
+                "Tj" | "TJ" | "'" | "\"" => return false,
+                "Do" => paints_xobjects = true,
+                _ => {}
+            }
+        }
+    }
+    paints_xobjects
+}
+
+/// Number of pages in the document's page tree, or `None` if it can't be parsed.
+pub(crate) fn page_count(path: &Path) -> Option<usize> {
+    Document::load(path).ok().map(|doc| doc.get_pages().len())
+}
+
+/// Binds the PDFium renderer, preferring a library bundled with the app over
+/// the system one. Bundled copies are looked up in the resource dir, then next
+/// to the executable; never relative to the working directory, which for an
+/// installed app is `/` or wherever it was launched from. Fails with
+/// `RenderingUnavailable` when no library is found.
+pub(crate) fn bind_pdfium(app: &tauri::AppHandle) -> Result<Pdfium, AppError> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    for dir in [app.path().resource_dir().ok(), exe_dir].into_iter().flatten() {
+        let library = Pdfium::pdfium_platform_library_name_at_path(&dir);
+        if let Ok(bindings) = Pdfium::bind_to_library(library) {
+            return Ok(Pdfium::new(bindings));
+        }
+    }
+    let bindings = Pdfium::bind_to_system_library()
+        .map_err(|e| AppError::RenderingUnavailable(e.to_string()))?;
+    Ok(Pdfium::new(bindings))
+}
+
+/// Name trees nested deeper than this are treated as malformed.
+const MAX_NAME_TREE_DEPTH: usize = 32;
+
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    match object {
+        Object::Reference(id) => doc.get_object(*id).ok(),
+        other => Some(other),
+    }
+}
+
+/// Root node of `/Root /Names /EmbeddedFiles`, if the document has one.
+fn embedded_files_tree(doc: &Document) -> Option<&Dictionary> {
+    let catalog = doc.catalog().ok()?;
+    let names = resolve(doc, catalog.get(b"Names").ok()?)?.as_dict().ok()?;
+    resolve(doc, names.get(b"EmbeddedFiles").ok()?)?.as_dict().ok()
+}
+
+/// Walks a name-tree node, collecting `(file name, decoded bytes)` for each
+/// file specification with an embedded stream. Unreadable entries are skipped.
+fn collect_file_specs(
+    doc: &Document,
+    node: &Dictionary,
+    out: &mut Vec<(String, Vec<u8>)>,
+    depth: usize,
+) {
+    if depth > MAX_NAME_TREE_DEPTH {
+        return;
+    }
+    if let Ok(Object::Array(kids)) = node.get(b"Kids") {
+        for kid in kids {
+            if let Some(kid) = resolve(doc, kid).and_then(|k| k.as_dict().ok()) {
+                collect_file_specs(doc, kid, out, depth + 1);
+            }
+        }
+    }
+
+    let Ok(Object::Array(names)) = node.get(b"Names") else {
+        return;
+    };
+    for pair in names.chunks_exact(2) {
+        let Some(spec) = resolve(doc, &pair[1]).and_then(|s| s.as_dict().ok()) else {
+            continue;
+        };
+        let name = info_string(spec, b"UF")
+            .or_else(|| info_string(spec, b"F"))
+            .or_else(|| match &pair[0] {
+                Object::String(bytes, _) => Some(String::from_utf8_lossy(bytes).to_string()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let stream = spec
+            .get(b"EF")
+            .ok()
+            .and_then(|ef| resolve(doc, ef))
+            .and_then(|ef| ef.as_dict().ok())
+            .and_then(|ef| ef.get(b"UF").or_else(|_| ef.get(b"F")).ok())
+            .and_then(|file| resolve(doc, file))
+            .and_then(|file| file.as_stream().ok());
+        if let Some(stream) = stream {
+            let content = stream
+                .decompressed_content()
+                .unwrap_or_else(|_| stream.content.clone());
+            out.push((name, content));
+        }
+    }
+}
+
+/// Page attributes a page may inherit from its ancestors in the page tree.
+const INHERITABLE_PAGE_KEYS: &[&[u8]] = &[b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Builds one document holding every page of `documents`, in order. Pages are
+/// re-parented under a single page tree, so inherited attributes are first
+/// copied onto each page. Outlines are dropped since their targets change.
+fn merge_documents(documents: Vec<Document>) -> Result<Document, AppError> {
+    let mut merged = Document::with_version("1.5");
+    let mut pages: Vec<(ObjectId, Dictionary)> = Vec::new();
+    let mut catalog: Option<(ObjectId, Dictionary)> = None;
+    let mut pages_root: Option<(ObjectId, Dictionary)> = None;
+    let mut next_id = 1;
+
+    for mut doc in documents {
+        doc.renumber_objects_with(next_id);
+        next_id = doc.max_id + 1;
+
+        for page_id in doc.get_pages().into_values() {
+            let mut page = doc
+                .get_dictionary(page_id)
+                .map_err(|e| AppError::ExtractionFailed(e.to_string()))?
+                .clone();
+            inherit_page_attributes(&doc, &mut page);
+            pages.push((page_id, page));
+        }
+
+        for (id, object) in doc.objects {
+            match object.type_name().unwrap_or("") {
+                "Catalog" => {
+                    if catalog.is_none() {
+                        catalog = object.as_dict().ok().map(|dict| (id, dict.clone()));
+                    }
+                }
+                "Pages" => {
+                    if pages_root.is_none() {
+                        pages_root = object.as_dict().ok().map(|dict| (id, dict.clone()));
+                    }
+                }
+                "Page" | "Outlines" | "Outline" => {}
+                _ => {
+                    merged.objects.insert(id, object);
+                }
+            }
+        }
+    }
+
+    let (catalog_id, mut catalog) =
+        catalog.ok_or_else(|| AppError::ExtractionFailed("PDF sem catalogo".to_string()))?;
+    let (pages_id, mut pages_root) =
+        pages_root.ok_or_else(|| AppError::ExtractionFailed("PDF sem arvore de paginas".to_string()))?;
+
+    let mut kids = Vec::with_capacity(pages.len());
+    for (page_id, mut page) in pages {
+        page.set("Parent", pages_id);
+        merged.objects.insert(page_id, Object::Dictionary(page));
+        kids.push(Object::Reference(page_id));
+    }
+
+    pages_root.set("Count", kids.len() as u32);
+    pages_root.set("Kids", kids);
+    pages_root.remove(b"Parent");
+    merged.objects.insert(pages_id, Object::Dictionary(pages_root));
+
+    catalog.set("Pages", pages_id);
+    catalog.remove(b"Outlines");
+    merged.objects.insert(catalog_id, Object::Dictionary(catalog));
+
+    merged.trailer.set("Root", catalog_id);
+    merged.max_id = next_id;
+    merged.renumber_objects();
+    merged.compress();
+    Ok(merged)
+}
+
+/// Copies inheritable attributes the page lacks from the nearest ancestor that has them.
+fn inherit_page_attributes(doc: &Document, page: &mut Dictionary) {
+    for &key in INHERITABLE_PAGE_KEYS {
+        if page.has(key) {
+            continue;
+        }
+        let mut parent = page.get(b"Parent").and_then(Object::as_reference).ok();
+        while let Some(parent_id) = parent {
+            let Ok(node) = doc.get_dictionary(parent_id) else {
+                break;
+            };
+            if let Ok(value) = node.get(key) {
+                page.set(key, value.clone());
+                break;
+            }
+            parent = node.get(b"Parent").and_then(Object::as_reference).ok();
+        }
+    }
+}
+
+/// Renders `page` with `config` and encodes the bitmap as PNG.
+pub(crate) fn render_png(page: &PdfPage, config: &PdfRenderConfig) -> Result<Vec<u8>, AppError> {
+    let image = page
+        .render_with_config(config)
+        .map_err(|e| AppError::RenderingUnavailable(e.to_string()))?
+        .as_image();
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| AppError::ExtractionFailed(e.to_string()))?;
+    Ok(png)
+}
+
+pub(crate) fn load_document(path: &Path) -> Result<Document, AppError> {
+    if !path.is_file() {
+        return Err(AppError::FileNotFound(path.to_string_lossy().to_string()));
+    }
+    // Open the file ourselves so a file locked by another program surfaces as
+    // `FileLocked` instead of an opaque parser error.
+    let file = std::fs::File::open(path).at_path(path)?;
+    Document::load_from(file)
+        .map_err(|e| AppError::ExtractionFailed(format!("{}: {}", path.display(), e)))
+}
+
+/// Reads a text string from the Info dictionary, decoding UTF-16BE when it has a BOM.
+fn info_string(dict: &Dictionary, key: &[u8]) -> Option<String> {
+    let bytes = match dict.get(key).ok()? {
+        Object::String(bytes, _) => bytes,
+        _ => return None,
+    };
+
+    let text = if bytes.starts_with(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = bytes[2..]
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        // PDFDocEncoding matches Latin-1 for the printable range.
+        bytes.iter().map(|&b| b as char).collect()
+    };
+
+    let text = text.trim_matches(char::from(0)).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Converts a PDF date (`D:YYYYMMDDHHmmSSOHH'mm'`) to the ISO-8601 form used by `format_time`.
+fn parse_pdf_date(raw: &str) -> Option<String> {
+    let raw = raw.trim().trim_start_matches("D:");
+    let digits: String = raw.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 4 {
+        return None;
+    }
+
+    let part = |start: usize, len: usize, default: u32| -> Option<u32> {
+        match digits.get(start..start + len) {
+            Some(s) => s.parse().ok(),
+            None => Some(default),
+        }
+    };
+    let date = NaiveDate::from_ymd_opt(
+        digits[0..4].parse().ok()?,
+        part(4, 2, 1)?,
+        part(6, 2, 1)?,
+    )?;
+    let naive = date.and_hms_opt(part(8, 2, 0)?, part(10, 2, 0)?, part(12, 2, 0)?)?;
+
+    let tz = &raw[digits.len()..];
+    let offset_seconds = match tz.chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let tz_digits: String = tz[1..].chars().filter(|c| c.is_ascii_digit()).collect();
+            let hours: i32 = tz_digits.get(0..2).and_then(|h| h.parse().ok()).unwrap_or(0);
+            let minutes: i32 = tz_digits.get(2..4).and_then(|m| m.parse().ok()).unwrap_or(0);
+            let seconds = hours * 3600 + minutes * 60;
+            if sign == '-' { -seconds } else { seconds }
+        }
+        _ => 0,
+    };
+
+    let datetime = FixedOffset::east_opt(offset_seconds)?
+        .from_local_datetime(&naive)
+        .single()?;
+    Some(format_time(datetime.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Stream};
+
+    /// Document with one page per entry of `pages`, each showing that text.
+    fn document_with_pages(pages: &[&str]) -> Document {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        // By reference: lopdf only follows inherited resources that are.
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let mut kids = Vec::new();
+        for text in pages {
+            let content = Content {
+                operations: vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![72.into(), 720.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*text)]),
+                    Operation::new("ET", vec![]),
+                ],
+            };
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            kids.push(Object::from(doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            })));
+        }
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        }));
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        doc
+    }
+
+    /// Embeds `files` through the catalog's `EmbeddedFiles` name tree.
+    fn attach(doc: &mut Document, files: &[(&str, &[u8])]) {
+        let mut names = Vec::new();
+        for &(name, bytes) in files {
+            let file_id = doc.add_object(Stream::new(
+                dictionary! { "Type" => "EmbeddedFile" },
+                bytes.to_vec(),
+            ));
+            let spec_id = doc.add_object(dictionary! {
+                "Type" => "Filespec",
+                "F" => Object::string_literal(name),
+                "EF" => dictionary! { "F" => file_id },
+            });
+            names.push(Object::string_literal(name));
+            names.push(spec_id.into());
+        }
+        doc.catalog_mut()
+            .unwrap()
+            .set("Names", dictionary! { "EmbeddedFiles" => dictionary! { "Names" => names } });
+    }
+
+    fn save(doc: &mut Document, path: &Path) -> String {
+        doc.save(path).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    /// One-page PDF written by hand, kept in the repo so extraction is checked
+    /// against a file this crate didn't produce.
+    const SAMPLE_PDF: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/peticao.pdf");
+
+    #[tokio::test]
+    async fn extracts_the_text_of_the_sample_pdf() {
+        let text = extract_pdf_text(SAMPLE_PDF.to_string(), None, None, None).await.unwrap();
+        assert!(text.contains("Excelentissimo Senhor Juiz de Direito"), "{:?}", text);
+    }
+
+    #[test]
+    fn a_text_pdf_is_not_scanned() {
+        assert!(!is_scanned(&document_with_pages(&["Sentenca"])));
+    }
+
+    #[test]
+    fn an_image_only_pdf_is_scanned() {
+        let mut doc = document_with_pages(&["Sentenca"]);
+        let image_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 1,
+                "Height" => 1,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            vec![0],
+        ));
+        let scan = Content {
+            operations: vec![
+                Operation::new("q", vec![]),
+                Operation::new("cm", vec![
+                    595.into(), 0.into(), 0.into(), 842.into(), 0.into(), 0.into(),
+                ]),
+                Operation::new("Do", vec!["Im1".into()]),
+                Operation::new("Q", vec![]),
+            ],
+        };
+        let (_, page_id) = doc.get_pages().into_iter().next().unwrap();
+        let content_id = doc.add_object(Stream::new(dictionary! {}, scan.encode().unwrap()));
+        let page = doc.get_object_mut(page_id).unwrap().as_dict_mut().unwrap();
+        page.set("Contents", content_id);
+        page.set("Resources", dictionary! { "XObject" => dictionary! { "Im1" => image_id } });
+
+        assert!(is_scanned(&doc));
+    }
+
+    #[tokio::test]
+    async fn pages_come_back_in_order_with_blank_pages_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut doc = document_with_pages(&["Primeira folha", "", "Terceira folha"]);
+        let path = save(&mut doc, &dir.path().join("autos.pdf"));
+
+        let pages = extract_pdf_pages(path).await.unwrap();
+        let pages: Vec<&str> = pages.iter().map(|page| page.trim()).collect();
+        assert_eq!(pages, ["Primeira folha", "", "Terceira folha"]);
+    }
+
+    #[tokio::test]
+    async fn merged_page_count_is_the_sum_of_the_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = document_with_pages(&["Inicial", "Procuracao"]);
+        let mut second = document_with_pages(&["Contestacao", "Replica", "Sentenca"]);
+        let first = save(&mut first, &dir.path().join("a.pdf"));
+        let second = save(&mut second, &dir.path().join("b.pdf"));
+        let output = dir.path().join("autos.pdf");
+
+        let output_path = output.to_string_lossy().to_string();
+        let merged = merge_pdfs(vec![first, second], output_path).await.unwrap();
+        assert_eq!(merged.page_count, Some(5));
+        let pages = extract_pdf_pages(merged.full_path).await.unwrap();
+        let pages: Vec<&str> = pages.iter().map(|page| page.trim()).collect();
+        assert_eq!(pages, ["Inicial", "Procuracao", "Contestacao", "Replica", "Sentenca"]);
+    }
+
+    #[tokio::test]
+    async fn an_existing_output_is_never_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = save(&mut document_with_pages(&["Inicial"]), &dir.path().join("a.pdf"));
+        let output = dir.path().join("autos.pdf");
+        std::fs::write(&output, b"existente").unwrap();
+
+        let output_path = output.to_string_lossy().to_string();
+        let err = merge_pdfs(vec![input], output_path).await.unwrap_err();
+        assert_eq!(err.code(), "ALREADY_EXISTS");
+        // A file created after the early check is caught when saving.
+        let err = save_new(&mut document_with_pages(&["Inicial"]), &output).unwrap_err();
+        assert_eq!(err.code(), "ALREADY_EXISTS");
+        assert_eq!(std::fs::read(&output).unwrap(), b"existente");
+    }
+
+    #[tokio::test]
+    async fn split_writes_each_range_to_its_own_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut doc = document_with_pages(&["Um", "Dois", "Tres", "Quatro", "Cinco"]);
+        let source = save(&mut doc, &dir.path().join("lote.pdf"));
+        let out = dir.path().to_string_lossy().to_string();
+
+        let parts = split_pdf(source, vec![(1, 2), (3, 5)], out).await.unwrap();
+        let names: Vec<_> = parts.iter().map(|part| part.name.as_str()).collect();
+        assert_eq!(names, ["lote_p1-2.pdf", "lote_p3-5.pdf"]);
+        let mut texts = Vec::new();
+        for part in parts {
+            let pages = extract_pdf_pages(part.full_path).await.unwrap();
+            texts.push(pages.iter().map(|page| page.trim().to_string()).collect::<Vec<_>>());
+        }
+        assert_eq!(texts, [vec!["Um", "Dois"], vec!["Tres", "Quatro", "Cinco"]]);
+    }
+
+    #[test]
+    fn split_ranges_must_fit_and_not_overlap() {
+        assert!(validate_ranges(&[(1, 2), (3, 5)], 5).is_ok());
+        assert!(validate_ranges(&[(3, 5), (1, 2)], 5).is_ok());
+        for ranges in [vec![], vec![(0, 1)], vec![(2, 1)], vec![(4, 6)], vec![(1, 3), (3, 5)]] {
+            let err = validate_ranges(&ranges, 5).unwrap_err();
+            assert_eq!(err.code(), "INVALID_ARGUMENT", "{:?}", ranges);
+        }
+    }
+
+    #[test]
+    fn a_preview_stops_reading_once_it_has_enough_text() {
+        let preview = |max_chars| {
+            let mut read = Vec::new();
+            let text = read_prefix(1..=500, max_chars, |page| {
+                read.push(page);
+                format!("Pagina {} dos autos, ação.\n", page)
+            });
+            (text, read)
+        };
+        // Cut inside the first page, right after a two-byte character.
+        assert_eq!(preview(23), ("Pagina 1 dos autos, açã".to_string(), vec![1]));
+        assert_eq!(preview(30), ("Pagina 1 dos autos, ação.\nPagi".to_string(), vec![1, 2]));
+    }
+
+    #[tokio::test]
+    async fn an_extraction_past_its_timeout_fails_with_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let pages = vec!["Folha dos autos com texto suficiente para demorar"; 300];
+        let path = save(&mut document_with_pages(&pages), &dir.path().join("longo.pdf"));
+
+        // A zero limit has passed before the parser thread can load 300 pages.
+        let err = extract_pdf_text(path.clone(), None, Some(0), None).await.unwrap_err();
+        assert_eq!(err.code(), "EXTRACTION_FAILED");
+        assert!(err.to_string().contains("timeout"), "{}", err);
+        let text = extract_pdf_text(path, None, Some(60), None).await.unwrap();
+        assert_eq!(text.matches("Folha dos autos").count(), 300);
+    }
+
+    #[test]
+    fn words_come_back_with_top_left_boxes() {
+        // PDFium is loaded at runtime; without a system copy there is nothing to run.
+        let Ok(bindings) = Pdfium::bind_to_system_library() else {
+            return;
+        };
+        let pdfium = Pdfium::new(bindings);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sentenca.pdf");
+        save(&mut document_with_pages(&["Julgo procedente o pedido"]), &path);
+
+        let words = word_positions(&pdfium, &path, 1).unwrap();
+        let texts: Vec<_> = words.iter().map(|word| word.text.as_str()).collect();
+        assert_eq!(texts, ["Julgo", "procedente", "o", "pedido"]);
+        for word in &words {
+            assert!(word.x > 0.0 && word.y > 0.0, "{:?}", word);
+            assert!(word.width > 0.0 && word.height > 0.0, "{:?}", word);
+        }
+        // The line sits 720pt up from the bottom of an 842pt page, so near the top.
+        assert!((words[0].x - 72.0).abs() < 1.0, "{:?}", words[0]);
+        assert!(words[0].y < 842.0 / 2.0, "{:?}", words[0]);
+        assert!(words[1].x > words[0].x + words[0].width);
+
+        let err = word_positions(&pdfium, &path, 2).unwrap_err();
+        assert_eq!(err.code(), "INVALID_ARGUMENT");
+    }
+
+    #[test]
+    fn chunks_cover_every_page_once_in_order() {
+        let labels: Vec<String> = (1..=1000).map(|page| format!("[folha {}]", page)).collect();
+        let pages: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let doc = document_with_pages(&pages);
+
+        let mut chunks = Vec::new();
+        let processed = extract_in_chunks(&doc, "autos.pdf", 64, |chunk| chunks.push(chunk));
+        assert_eq!(processed.unwrap(), 1000);
+        assert_eq!(chunks.len(), 16);
+        let mut next_page = 1;
+        for chunk in &chunks {
+            assert_eq!(chunk.start_page, next_page);
+            assert!(chunk.end_page < chunk.start_page + 64);
+            for page in 1..=1000 {
+                let inside = (chunk.start_page..=chunk.end_page).contains(&page);
+                assert_eq!(chunk.text.contains(&labels[page - 1]), inside, "folha {}", page);
+            }
+            next_page = chunk.end_page + 1;
+        }
+        assert_eq!(next_page, 1001);
+    }
+
+    #[tokio::test]
+    async fn detects_portuguese_and_english_and_reports_missing_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let samples = [
+            ("por", "O autor requer a procedencia do pedido para condenar o reu ao pagamento \
+                     da indenizacao pelos danos morais sofridos, com juros e correcao monetaria."),
+            ("eng", "The plaintiff asks the court to enter judgment against the defendant for \
+                     the damages suffered, together with interest and the costs of this action."),
+        ];
+        for (expected, text) in samples {
+            let path = save(&mut document_with_pages(&[text]), &dir.path().join("a.pdf"));
+            let detection = detect_pdf_language(path).await.unwrap();
+            assert!(detection.has_text);
+            assert_eq!(detection.language.as_deref(), Some(expected), "{:?}", detection);
+            assert!(detection.confidence > 0.5, "{:?}", detection);
+        }
+
+        let blank = save(&mut document_with_pages(&[""]), &dir.path().join("b.pdf"));
+        let detection = detect_pdf_language(blank).await.unwrap();
+        assert!(!detection.has_text && detection.language.is_none(), "{:?}", detection);
+    }
+
+    #[test]
+    fn cleaning_joins_hyphenated_words_and_expands_ligatures() {
+        let raw = "O pro-\r\ncesso tra-\n  mita no Rio-\nGrande do Sul.\n\n\n\n\
+                   O a\u{FB03}davit de\u{FB01}ni\u{AD}tivo   \u{7}\u{FB02}ui.\n";
+        assert_eq!(
+            clean_text(raw),
+            "O processo tramita no Rio-Grande do Sul.\n\nO affidavit definitivo flui."
+        );
+    }
+
+    #[test]
+    fn similarity_ranks_identical_similar_and_unrelated_texts() {
+        let dir = tempfile::tempdir().unwrap();
+        let texts = [
+            "O reu foi citado e apresentou contestacao no prazo legal alegando prescricao",
+            "O reu foi citado e apresentou contestacao fora do prazo legal alegando prescricao",
+            "Trata-se de inventario dos bens deixados pelo falecido aos seus herdeiros",
+            "",
+        ];
+        let extracted: Vec<(String, String)> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let path = dir.path().join(format!("{}.pdf", i));
+                save(&mut document_with_pages(&[text]), &path);
+                let text = extract_text(&path).unwrap();
+                (path.to_string_lossy().to_string(), text)
+            })
+            .collect();
+        let score = |i: usize, j: usize| {
+            let (a, b) = (&extracted[i], &extracted[j]);
+            text_similarity((&a.0, &a.1), (&b.0, &b.1))
+        };
+
+        assert_eq!(score(0, 0).unwrap(), 1.0);
+        let similar = score(0, 1).unwrap();
+        assert!(similar > 0.3 && similar < 1.0, "{}", similar);
+        assert_eq!(score(1, 0).unwrap(), similar);
+        assert_eq!(score(0, 2).unwrap(), 0.0);
+        assert_eq!(score(0, 3).unwrap_err().code(), "EXTRACTION_FAILED");
+    }
+
+    #[tokio::test]
+    async fn total_pages_sum_the_tree_and_corrupt_files_count_as_zero() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("2024").join("recursos");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::copy(SAMPLE_PDF, dir.path().join("peticao.pdf")).unwrap();
+        save(&mut document_with_pages(&["Um", "Dois"]), &dir.path().join("2024").join("a.pdf"));
+        save(&mut document_with_pages(&["Um", "Dois", "Tres"]), &nested.join("b.pdf"));
+        let corrupt = nested.join("corrompido.pdf");
+        std::fs::write(&corrupt, b"%PDF-1.4\nnao e um pdf").unwrap();
+
+        let folder = dir.path().to_string_lossy().to_string();
+        let summary = total_page_count(folder).await.unwrap();
+        assert_eq!(summary.total_pages, 6);
+        assert_eq!(summary.file_count, 4);
+        assert_eq!(summary.corrupt, [corrupt.to_string_lossy().to_string()]);
+    }
+
+    #[tokio::test]
+    async fn extracts_the_single_attachment() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut doc = document_with_pages(&["Peticao inicial"]);
+        attach(&mut doc, &[("planilha.csv", b"valor;data\n10;2024-01-01\n")]);
+        let source = save(&mut doc, &dir.path().join("peticao.pdf"));
+        let out = dir.path().join("anexos");
+        std::fs::create_dir(&out).unwrap();
+
+        let out_dir = out.to_string_lossy().to_string();
+        let files = extract_pdf_attachments(source, out_dir).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "planilha.csv");
+        let written = std::fs::read(out.join("planilha.csv")).unwrap();
+        assert_eq!(written, b"valor;data\n10;2024-01-01\n");
+    }
+
+    #[tokio::test]
+    async fn no_attachments_is_an_empty_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = save(&mut document_with_pages(&["Sem anexos"]), &dir.path().join("a.pdf"));
+
+        let out = dir.path().to_string_lossy().to_string();
+        assert!(extract_pdf_attachments(source, out).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn repeated_and_existing_attachment_names_get_suffixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut doc = document_with_pages(&["Autos"]);
+        attach(&mut doc, &[("laudo.txt", b"primeiro"), ("laudo.txt", b"segundo")]);
+        let source = save(&mut doc, &dir.path().join("autos.pdf"));
+        let out = dir.path().join("anexos");
+        std::fs::create_dir(&out).unwrap();
+        std::fs::write(out.join("laudo.txt"), b"existente").unwrap();
+
+        let out_dir = out.to_string_lossy().to_string();
+        let files = extract_pdf_attachments(source, out_dir).await.unwrap();
+        let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["laudo (1).txt", "laudo (2).txt"]);
+        assert_eq!(std::fs::read(out.join("laudo.txt")).unwrap(), b"existente");
+        assert_eq!(std::fs::read(out.join("laudo (2).txt")).unwrap(), b"segundo");
+    }
+
+    /// Padding string from the PDF spec's standard security handler.
+    const PASSWORD_PAD: [u8; 32] = [
+        0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA, 0x01,
+        0x08, 0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE, 0x64, 0x53,
+        0x69, 0x7A,
+    ];
+
+    fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut state: Vec<u8> = (0..=255).collect();
+        let mut j = 0u8;
+        for i in 0..256 {
+            j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+            state.swap(i, j as usize);
+        }
+        let (mut i, mut j) = (0u8, 0u8);
+        data.iter()
+            .map(|byte| {
+                i = i.wrapping_add(1);
+                j = j.wrapping_add(state[i as usize]);
+                state.swap(i as usize, j as usize);
+                byte ^ state[state[i as usize].wrapping_add(state[j as usize]) as usize]
+            })
+            .collect()
+    }
+
+    /// Encrypts `doc` in place with 40-bit RC4 (V1/R2) under user `password`.
+    fn encrypt_rc4(doc: &mut Document, password: &str) {
+        let id = Object::string_literal(b"0123456789abcdef".to_vec());
+        doc.trailer.set("ID", vec![id.clone(), id]);
+        let encrypt_id = doc.add_object(dictionary! {
+            "Filter" => "Standard",
+            "V" => 1,
+            "R" => 2,
+            "Length" => 40,
+            "P" => -4,
+            "O" => Object::string_literal(PASSWORD_PAD.to_vec()),
+        });
+        doc.trailer.set("Encrypt", encrypt_id);
+
+        let key = lopdf::encryption::get_encryption_key(doc, password, false).unwrap();
+        let user_check = Object::string_literal(rc4(&key, &PASSWORD_PAD));
+        doc.get_object_mut(encrypt_id).unwrap().as_dict_mut().unwrap().set("U", user_check);
+        // RC4 is symmetric, so "decrypting" plaintext with the object key encrypts it.
+        for (&id, object) in doc.objects.iter_mut().filter(|(&id, _)| id != encrypt_id) {
+            let Ok(sealed) = lopdf::encryption::decrypt_object(&key, id, object) else {
+                continue;
+            };
+            match object {
+                Object::Stream(stream) => stream.set_content(sealed),
+                Object::String(content, _) => *content = sealed,
+                _ => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn validation_tells_valid_truncated_and_encrypted_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let valid = validate_pdf(SAMPLE_PDF.to_string()).await.unwrap();
+        assert!(valid.is_valid && !valid.encrypted, "{:?}", valid);
+        assert_eq!(valid.page_count, Some(1));
+        assert!(valid.warnings.is_empty(), "{:?}", valid.warnings);
+
+        let bytes = std::fs::read(SAMPLE_PDF).unwrap();
+        let truncated = dir.path().join("truncado.pdf");
+        std::fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+        let report = validate_pdf(truncated.to_string_lossy().to_string()).await.unwrap();
+        assert!(!report.is_valid, "{:?}", report);
+        assert!(report.warnings.iter().any(|w| w.contains("%%EOF")), "{:?}", report.warnings);
+
+        let mut doc = document_with_pages(&["Peticao sigilosa"]);
+        encrypt_rc4(&mut doc, "segredo");
+        let sealed = save(&mut doc, &dir.path().join("sigiloso.pdf"));
+        let report = validate_pdf(sealed).await.unwrap();
+        assert!(report.encrypted, "{:?}", report);
+    }
+
+    #[tokio::test]
+    async fn decrypts_with_the_right_password() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut doc = document_with_pages(&["Peticao sigilosa"]);
+        encrypt_rc4(&mut doc, "segredo");
+        let input = save(&mut doc, &dir.path().join("sigiloso.pdf"));
+        let sealed = load_document(Path::new(&input)).unwrap();
+        assert!(!document_text(&sealed, Path::new(&input)).unwrap().contains("sigilosa"));
+        let output = dir.path().join("aberto.pdf");
+
+        let output_path = output.to_string_lossy().to_string();
+        decrypt_pdf(input, "segredo".to_string(), output_path).await.unwrap();
+        let opened = load_document(&output).unwrap();
+        assert!(!opened.is_encrypted());
+        assert!(document_text(&opened, &output).unwrap().contains("Peticao sigilosa"));
+    }
+
+    #[tokio::test]
+    async fn a_wrong_password_is_reported_as_such() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut doc = document_with_pages(&["Peticao sigilosa"]);
+        encrypt_rc4(&mut doc, "segredo");
+        let input = save(&mut doc, &dir.path().join("sigiloso.pdf"));
+
+        let output = dir.path().join("aberto.pdf").to_string_lossy().to_string();
+        let err = decrypt_pdf(input, "errada".to_string(), output).await.unwrap_err();
+        assert_eq!(err.code(), "WRONG_PASSWORD");
+        assert!(!dir.path().join("aberto.pdf").exists());
+    }
+
+    #[tokio::test]
+    async fn decrypting_never_overwrites_an_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut doc = document_with_pages(&["Peticao sigilosa"]);
+        encrypt_rc4(&mut doc, "segredo");
+        let input = save(&mut doc, &dir.path().join("sigiloso.pdf"));
+        let output = dir.path().join("aberto.pdf");
+        std::fs::write(&output, b"existente").unwrap();
+
+        let output_path = output.to_string_lossy().to_string();
+        let err = decrypt_pdf(input, "segredo".to_string(), output_path).await.unwrap_err();
+        assert_eq!(err.code(), "ALREADY_EXISTS");
+        assert_eq!(std::fs::read(&output).unwrap(), b"existente");
+    }
+
+    #[tokio::test]
+    async fn aes_encryption_is_unsupported_not_a_wrong_password() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut doc = document_with_pages(&["Peticao sigilosa"]);
+        doc.trailer.set("ID", vec![Object::string_literal("id"), Object::string_literal("id")]);
+        let encrypt_id = doc.add_object(dictionary! {
+            "Filter" => "Standard",
+            "V" => 4,
+            "R" => 4,
+            "Length" => 128,
+            "P" => -4,
+            "O" => Object::string_literal(PASSWORD_PAD.to_vec()),
+            "U" => Object::string_literal(PASSWORD_PAD.to_vec()),
+            "CF" => dictionary! { "StdCF" => dictionary! { "CFM" => "AESV2", "Length" => 16 } },
+            "StmF" => "StdCF",
+            "StrF" => "StdCF",
+        });
+        doc.trailer.set("Encrypt", encrypt_id);
+        let input = save(&mut doc, &dir.path().join("aes.pdf"));
+
+        let output = dir.path().join("aberto.pdf").to_string_lossy().to_string();
+        let err = decrypt_pdf(input, "qualquer".to_string(), output).await.unwrap_err();
+        assert_eq!(err.code(), "EXTRACTION_FAILED");
+    }
+}
@@ -0,0 +1,50 @@
+use crate::commands::{cache, pdf};
+use crate::models::{AppError, HashAlgorithm};
+use pdfium_render::prelude::PdfRenderConfig;
+use std::path::Path;
+
+/// PNG of the first page scaled to fit a `max_dimension` square, for use as a
+/// data URL in the file list. Renders are cached by content hash, so renamed or
+/// moved files reuse them. Fails with `RenderingUnavailable` when PDFium can't
+/// be loaded on this platform.
+#[tauri::command]
+pub async fn generate_thumbnail(
+    app: tauri::AppHandle,
+    file_path: String,
+    max_dimension: u32,
+) -> Result<Vec<u8>, AppError> {
+    let path = Path::new(&file_path);
+    if max_dimension == 0 {
+        return Err(AppError::InvalidArgument("max_dimension deve ser maior que zero".to_string()));
+    }
+
+    let file_hash = cache::hash_path(path, HashAlgorithm::Sha256)?;
+    if let Some(png) = cache::get_thumbnail(&app, &file_hash, max_dimension)? {
+        return Ok(png);
+    }
+
+    let png = render_first_page(&app, path, max_dimension)?;
+    cache::save_thumbnail(&app, &file_hash, max_dimension, &png)?;
+    Ok(png)
+}
+
+fn render_first_page(
+    app: &tauri::AppHandle,
+    path: &Path,
+    max_dimension: u32,
+) -> Result<Vec<u8>, AppError> {
+    let pdfium = pdf::bind_pdfium(app)?;
+    let document = pdfium
+        .load_pdf_from_file(path, None)
+        .map_err(|e| AppError::ExtractionFailed(format!("{}: {}", path.display(), e)))?;
+    let page = document
+        .pages()
+        .first()
+        .map_err(|_| AppError::InvalidFile("PDF sem paginas".to_string()).at_path(path))?;
+
+    let max = max_dimension.min(i32::MAX as u32) as i32;
+    let config = PdfRenderConfig::new()
+        .set_target_width(max)
+        .set_maximum_height(max);
+    pdf::render_png(&page, &config)
+}
@@ -0,0 +1,194 @@
+use crate::commands::filesystem;
+use crate::models::{AppError, FolderChange, FolderChangeKind, FolderRescan, PdfFile};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+/// Events for the same path arriving within this window are coalesced.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Active watchers keyed by the folder path the frontend passed in, plus the
+/// last scan of each folder watched in re-scan mode. Managed as Tauri state.
+#[derive(Default)]
+pub struct WatcherRegistry {
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+    snapshots: Mutex<HashMap<String, Vec<PdfFile>>>,
+}
+
+/// How a watcher reports changes once a burst settles.
+struct WatchMode {
+    folder: String,
+    /// Re-scan and emit `folder-rescanned` instead of per-path `folder-changed`.
+    rescan: bool,
+    debounce: Duration,
+}
+
+/// Watches `folder_path` recursively. By default each PDF change is emitted as
+/// `folder-changed`. With `rescan`, the folder is re-listed once a burst of
+/// changes settles and a single `folder-rescanned` diff against the previous
+/// listing is emitted instead. `debounce_ms` sets the quiet period (500 ms).
+#[tauri::command]
+pub async fn watch_folder(
+    app: tauri::AppHandle,
+    registry: tauri::State<'_, WatcherRegistry>,
+    folder_path: String,
+    rescan: Option<bool>,
+    debounce_ms: Option<u64>,
+) -> Result<(), AppError> {
+    let path = Path::new(&folder_path);
+    if !path.is_dir() {
+        return Err(AppError::InvalidDirectory(folder_path));
+    }
+
+    let mode = WatchMode {
+        folder: folder_path.clone(),
+        rescan: rescan.unwrap_or(false),
+        debounce: debounce_ms.map(Duration::from_millis).unwrap_or(DEBOUNCE),
+    };
+    if mode.rescan {
+        let snapshot = filesystem::find_pdfs(path)?;
+        registry.snapshots.lock().unwrap().insert(folder_path.clone(), snapshot);
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+    watcher.watch(path, RecursiveMode::Recursive).map_err(watch_error)?;
+
+    // The forwarding thread exits once the watcher (and with it the sender) is dropped.
+    std::thread::spawn(move || forward_changes(app, rx, mode));
+    registry.watchers.lock().unwrap().insert(folder_path, watcher);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unwatch_folder(
+    registry: tauri::State<'_, WatcherRegistry>,
+    folder_path: String,
+) -> Result<(), AppError> {
+    registry.watchers.lock().unwrap().remove(&folder_path);
+    registry.snapshots.lock().unwrap().remove(&folder_path);
+    Ok(())
+}
+
+/// Collects PDF changes and reports them once the folder has been quiet for the
+/// debounce window, so a bulk copy produces one burst instead of a flood.
+fn forward_changes(
+    app: tauri::AppHandle,
+    rx: Receiver<notify::Result<notify::Event>>,
+    mode: WatchMode,
+) {
+    let mut pending: HashMap<PathBuf, FolderChangeKind> = HashMap::new();
+    loop {
+        let received = if pending.is_empty() {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(mode.debounce)
+        };
+
+        match received {
+            Ok(Ok(event)) => {
+                let kind = match event.kind {
+                    EventKind::Create(_) => FolderChangeKind::Added,
+                    EventKind::Remove(_) => FolderChangeKind::Removed,
+                    EventKind::Modify(_) => FolderChangeKind::Modified,
+                    _ => continue,
+                };
+                let pdfs = event
+                    .paths
+                    .into_iter()
+                    .filter(|p| filesystem::matching_extension(p, filesystem::PDF_ONLY).is_some());
+                for path in pdfs {
+                    pending
+                        .entry(path)
+                        .and_modify(|existing| {
+                            // A file created and then written is still just "added".
+                            if !(*existing == FolderChangeKind::Added && kind == FolderChangeKind::Modified) {
+                                *existing = kind;
+                            }
+                        })
+                        .or_insert(kind);
+                }
+            }
+            Ok(Err(_)) => {}
+            Err(RecvTimeoutError::Timeout) => flush(&app, &mode, &mut pending),
+            Err(RecvTimeoutError::Disconnected) => {
+                flush(&app, &mode, &mut pending);
+                break;
+            }
+        }
+    }
+}
+
+fn flush(
+    app: &tauri::AppHandle,
+    mode: &WatchMode,
+    pending: &mut HashMap<PathBuf, FolderChangeKind>,
+) {
+    if mode.rescan {
+        if !pending.is_empty() {
+            pending.clear();
+            rescan(app, &mode.folder);
+        }
+        return;
+    }
+    for (path, kind) in pending.drain() {
+        let _ = app.emit("folder-changed", FolderChange {
+            kind,
+            path: path.to_string_lossy().to_string(),
+        });
+    }
+}
+
+/// Re-lists `folder`, emits the diff against the stored snapshot as
+/// `folder-rescanned`, and keeps the new listing for next time. Nothing is
+/// emitted if the folder was unwatched meanwhile or can no longer be read.
+fn rescan(app: &tauri::AppHandle, folder: &str) {
+    let Ok(current) = filesystem::find_pdfs(Path::new(folder)) else {
+        return;
+    };
+    let registry = app.state::<WatcherRegistry>();
+    let previous = {
+        let mut snapshots = registry.snapshots.lock().unwrap();
+        let Some(previous) = snapshots.get_mut(folder) else {
+            return;
+        };
+        std::mem::replace(previous, current.clone())
+    };
+
+    let before: HashMap<&str, &PdfFile> = previous.iter().map(|f| (f.path.as_str(), f)).collect();
+    let after: HashMap<&str, &PdfFile> = current.iter().map(|f| (f.path.as_str(), f)).collect();
+    let diff = FolderRescan {
+        folder: folder.to_string(),
+        added: current
+            .iter()
+            .filter(|f| !before.contains_key(f.path.as_str()))
+            .cloned()
+            .collect(),
+        removed: previous
+            .iter()
+            .filter(|f| !after.contains_key(f.path.as_str()))
+            .map(|f| f.path.clone())
+            .collect(),
+        modified: current
+            .iter()
+            .filter(|f| {
+                before.get(f.path.as_str()).is_some_and(|old| {
+                    old.size_bytes != f.size_bytes || old.last_modified != f.last_modified
+                })
+            })
+            .cloned()
+            .collect(),
+    };
+
+    if !(diff.added.is_empty() && diff.removed.is_empty() && diff.modified.is_empty()) {
+        let _ = app.emit("folder-rescanned", diff);
+    }
+}
+
+fn watch_error(err: notify::Error) -> AppError {
+    AppError::IoError(err.to_string())
+}
@@ -1,11 +1,14 @@
 mod commands;
 mod models;
+mod settings;
 
-use commands::{filesystem, cache};
+use tauri::Manager;
+
+use commands::{filesystem, cache, pdf, extraction, watcher, backend, ocr, thumbnail};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let mut builder = tauri::Builder::default()
+    let builder = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
@@ -16,21 +19,98 @@ pub fn run() {
         .plugin(tauri_plugin_process::init());
 
     #[cfg(feature = "mcp-bridge")]
-    {
-        builder = builder.plugin(tauri_plugin_mcp_bridge::init());
-    }
+    let builder = builder.plugin(tauri_plugin_mcp_bridge::init());
 
     builder
+        .manage(filesystem::ScanRegistry::default())
+        .manage(cache::CacheDb::default())
+        .manage(watcher::WatcherRegistry::default())
+        .manage(extraction::ExtractionRegistry::default())
         .invoke_handler(tauri::generate_handler![
             filesystem::list_process_folders,
+            filesystem::list_process_folders_multi,
+            filesystem::list_changed_folders,
             filesystem::list_pdfs_in_folder,
+            filesystem::list_pdfs_in_folder_paged,
+            filesystem::cancel_scan,
+            filesystem::search_pdfs,
+            filesystem::find_duplicate_pdfs,
+            filesystem::folder_size_breakdown,
+            filesystem::export_folder_listing,
+            filesystem::trash_pdf,
+            filesystem::copy_pdf,
+            filesystem::move_pdf,
+            filesystem::rename_folder,
+            filesystem::normalize_folder_selection,
+            filesystem::reveal_in_file_manager,
+            filesystem::is_file_locked,
+            filesystem::read_file_range,
+            watcher::watch_folder,
+            watcher::unwatch_folder,
             cache::init_cache,
             cache::get_cached_result,
             cache::save_cached_result,
+            cache::save_cached_results_batch,
+            cache::get_cache_audit,
             cache::hash_file,
+            cache::hash_files,
+            cache::filter_uncached,
+            cache::quick_hash_file,
+            cache::hash_folder,
             cache::list_cache_entries,
+            cache::prune_expired_cache,
+            cache::prune_orphaned_cache,
+            cache::evict_cache,
+            cache::enforce_cache_size_limit,
+            cache::clear_cache,
+            cache::cache_stats,
+            cache::verify_cache,
+            cache::cache_disk_space,
+            cache::export_cache,
+            cache::import_cache,
+            cache::invalidate_cache_for_backend,
+            cache::list_cache_by_backend,
+            cache::save_extracted_text,
+            cache::get_extracted_text,
+            cache::cache_folder_result,
+            cache::get_folder_result,
+            cache::record_file_access,
+            cache::get_recent_files,
+            pdf::extract_pdf_text,
+            pdf::clean_extracted_text,
+            pdf::extract_pdf_pages,
+            pdf::extract_pdf_text_chunked,
+            pdf::extract_text_preview,
+            pdf::detect_pdf_language,
+            pdf::pdf_text_similarity,
+            pdf::extract_pdf_text_positions,
+            pdf::extract_pdf_metadata,
+            pdf::is_scanned_pdf,
+            pdf::total_page_count,
+            pdf::validate_pdf,
+            pdf::decrypt_pdf,
+            pdf::extract_pdf_attachments,
+            pdf::merge_pdfs,
+            pdf::split_pdf,
+            ocr::ocr_pdf,
+            thumbnail::generate_thumbnail,
+            extraction::extract_folder,
+            extraction::retry_failed_extractions,
+            extraction::extraction_status_summary,
+            extraction::extraction_progress,
+            extraction::estimate_extraction_time,
+            backend::call_backend,
+            backend::get_or_fetch_result,
+            backend::check_backend_health,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Not `ExitRequested`: that one can still be prevented, leaving the
+            // app running with its cache connection closed.
+            if let tauri::RunEvent::Exit = event {
+                app.state::<cache::CacheDb>().close();
+            }
+        });
 }
 
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// Result of `check_backend_health`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendHealth {
+    /// The backend answered at all, whatever the status.
+    pub reachable: bool,
+    /// Answered with a 2xx status.
+    pub healthy: bool,
+    pub status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    /// Why the request failed when it got no response.
+    pub error: Option<String>,
+}
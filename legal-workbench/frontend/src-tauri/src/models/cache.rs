@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub entry_count: u64,
+    pub total_response_bytes: u64,
+    pub oldest_cached_at: Option<i64>,
+    pub newest_cached_at: Option<i64>,
+    pub backend_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub file_hash: String,
+    pub file_path: String,
+    pub api_response: String,
+    pub backend_url: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchResult {
+    pub response: String,
+    pub from_cache: bool,
+}
+
+/// Space on the volume holding the cache db.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskSpace {
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    /// `available_bytes / total_bytes * 100`, or 0 for a zero-sized volume.
+    pub available_percent: f64,
+}
+
+/// One `api_cache` row as written by `export_cache` and read by `import_cache`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheExportRow {
+    /// Absent in exports made before namespaces existed.
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+    pub file_hash: String,
+    pub file_path: String,
+    pub api_response: String,
+    pub backend_url: String,
+    pub cached_at: i64,
+}
+
+fn default_namespace() -> String {
+    "default".to_string()
+}
+
+/// One row of the cache audit log, from `get_cache_audit`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheAuditEntry {
+    pub timestamp: i64,
+    pub namespace: String,
+    pub file_hash: String,
+    pub backend_url: String,
+    pub action: String,
+}
+
+/// One row from `list_cache_by_backend`. The response body is left out unless
+/// it was asked for by hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendCacheEntry {
+    pub file_hash: String,
+    pub file_path: String,
+    pub cached_at: i64,
+    /// Size as stored; gzip-compressed rows are smaller than the response.
+    pub response_bytes: u64,
+    pub compressed: bool,
+    pub api_response: Option<String>,
+}
+
+/// Report from `verify_cache`. Nothing is deleted; the lists say what to look at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheIntegrity {
+    /// SQLite's `integrity_check` passed.
+    pub integrity_ok: bool,
+    /// What `integrity_check` reported: `["ok"]` or the problems it found.
+    pub integrity_messages: Vec<String>,
+    pub row_count: u64,
+    pub empty_responses: Vec<String>,
+    /// Responses that aren't valid JSON, or compressed ones that don't decompress.
+    pub malformed_responses: Vec<String>,
+}
@@ -1,7 +1,9 @@
-use serde::Serialize;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::path::Path;
 use thiserror::Error;
 
-#[derive(Debug, Error, Serialize)]
+#[derive(Debug, Error)]
 pub enum AppError {
     #[error("Arquivo nao encontrado: {0}")]
     FileNotFound(String),
@@ -17,10 +19,153 @@ pub enum AppError {
 
     #[error("Erro de banco: {0}")]
     DatabaseError(String),
+
+    #[error("Varredura cancelada: {0}")]
+    Cancelled(String),
+
+    #[error("Falha na extracao: {0}")]
+    ExtractionFailed(String),
+
+    #[error("Arquivo invalido: {0}")]
+    InvalidFile(String),
+
+    #[error("Nome invalido: {0}")]
+    InvalidName(String),
+
+    #[error("Ja existe: {0}")]
+    AlreadyExists(String),
+
+    #[error("Erro de rede: {0}")]
+    NetworkError(String),
+
+    #[error("Renderizacao indisponivel: {0}")]
+    RenderingUnavailable(String),
+
+    #[error("Arquivo em uso por outro programa: {0}")]
+    FileLocked(String),
+
+    #[error("Caminho com codificacao invalida: {0}")]
+    InvalidPathEncoding(String),
+
+    #[error("Senha incorreta: {0}")]
+    WrongPassword(String),
+
+    #[error("PDF nao esta protegido: {0}")]
+    NotEncrypted(String),
+
+    #[error("Parametro invalido: {0}")]
+    InvalidArgument(String),
+
+    /// Any other error, tagged with the file it concerns.
+    #[error("{source}")]
+    WithPath { path: String, source: Box<AppError> },
+}
+
+impl AppError {
+    /// Stable machine-readable code; the frontend maps it to a localized message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::FileNotFound(_) => "FILE_NOT_FOUND",
+            AppError::PermissionDenied(_) => "PERMISSION_DENIED",
+            AppError::InvalidDirectory(_) => "INVALID_DIRECTORY",
+            AppError::IoError(_) => "IO_ERROR",
+            AppError::DatabaseError(_) => "DATABASE_ERROR",
+            AppError::Cancelled(_) => "CANCELLED",
+            AppError::ExtractionFailed(_) => "EXTRACTION_FAILED",
+            AppError::InvalidFile(_) => "INVALID_FILE",
+            AppError::InvalidName(_) => "INVALID_NAME",
+            AppError::AlreadyExists(_) => "ALREADY_EXISTS",
+            AppError::NetworkError(_) => "NETWORK_ERROR",
+            AppError::RenderingUnavailable(_) => "RENDERING_UNAVAILABLE",
+            AppError::FileLocked(_) => "FILE_LOCKED",
+            AppError::InvalidPathEncoding(_) => "INVALID_PATH_ENCODING",
+            AppError::WrongPassword(_) => "WRONG_PASSWORD",
+            AppError::NotEncrypted(_) => "NOT_ENCRYPTED",
+            AppError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            AppError::WithPath { source, .. } => source.code(),
+        }
+    }
+
+    /// Variant name, for frontend code that branches on the error type.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::FileNotFound(_) => "FileNotFound",
+            AppError::PermissionDenied(_) => "PermissionDenied",
+            AppError::InvalidDirectory(_) => "InvalidDirectory",
+            AppError::IoError(_) => "IoError",
+            AppError::DatabaseError(_) => "DatabaseError",
+            AppError::Cancelled(_) => "Cancelled",
+            AppError::ExtractionFailed(_) => "ExtractionFailed",
+            AppError::InvalidFile(_) => "InvalidFile",
+            AppError::InvalidName(_) => "InvalidName",
+            AppError::AlreadyExists(_) => "AlreadyExists",
+            AppError::NetworkError(_) => "NetworkError",
+            AppError::RenderingUnavailable(_) => "RenderingUnavailable",
+            AppError::FileLocked(_) => "FileLocked",
+            AppError::InvalidPathEncoding(_) => "InvalidPathEncoding",
+            AppError::WrongPassword(_) => "WrongPassword",
+            AppError::NotEncrypted(_) => "NotEncrypted",
+            AppError::InvalidArgument(_) => "InvalidArgument",
+            AppError::WithPath { source, .. } => source.kind(),
+        }
+    }
+
+    /// The file or directory the error concerns, when known. Variants whose
+    /// payload is the offending path report it, so the frontend can offer to
+    /// retry that file.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            AppError::WithPath { path, .. } => Some(path),
+            AppError::FileNotFound(path)
+            | AppError::InvalidDirectory(path)
+            | AppError::InvalidFile(path)
+            | AppError::AlreadyExists(path)
+            | AppError::WrongPassword(path)
+            | AppError::NotEncrypted(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    pub fn at_path(self, path: &Path) -> Self {
+        match self {
+            AppError::WithPath { .. } => self,
+            other => AppError::WithPath {
+                path: path.to_string_lossy().to_string(),
+                source: Box::new(other),
+            },
+        }
+    }
+}
+
+/// Attaches the offending path to a failed operation.
+pub trait PathContext<T> {
+    fn at_path(self, path: &Path) -> Result<T, AppError>;
+}
+
+impl<T, E: Into<AppError>> PathContext<T> for Result<T, E> {
+    fn at_path(self, path: &Path) -> Result<T, AppError> {
+        self.map_err(|e| Into::<AppError>::into(e).at_path(path))
+    }
+}
+
+/// Serializes as `{ code, kind, message, path }`, matching `TauriError` on the frontend.
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("path", &self.path())?;
+        state.end()
+    }
 }
 
 impl From<std::io::Error> for AppError {
     fn from(err: std::io::Error) -> Self {
+        // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION: another program has the file open.
+        if cfg!(windows) && matches!(err.raw_os_error(), Some(32 | 33)) {
+            return AppError::FileLocked(err.to_string());
+        }
         match err.kind() {
             std::io::ErrorKind::NotFound => AppError::FileNotFound(err.to_string()),
             std::io::ErrorKind::PermissionDenied => AppError::PermissionDenied(err.to_string()),
@@ -34,3 +179,43 @@ impl From<rusqlite::Error> for AppError {
         AppError::DatabaseError(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialized_path(err: AppError) -> serde_json::Value {
+        serde_json::to_value(err).unwrap()["path"].clone()
+    }
+
+    #[test]
+    fn path_carrying_variants_serialize_their_path() {
+        let path = "/docs/a.pdf";
+        for err in [
+            AppError::FileNotFound(path.to_string()),
+            AppError::InvalidDirectory(path.to_string()),
+            AppError::InvalidFile(path.to_string()),
+            AppError::AlreadyExists(path.to_string()),
+            AppError::WrongPassword(path.to_string()),
+            AppError::NotEncrypted(path.to_string()),
+        ] {
+            assert_eq!(serialized_path(err), path);
+        }
+    }
+
+    #[test]
+    fn wrapped_errors_report_the_outer_path() {
+        let err = AppError::InvalidFile("PDF protegido por senha".to_string())
+            .at_path(Path::new("/docs/a.pdf"));
+        let value = serde_json::to_value(err).unwrap();
+        assert_eq!(value["path"], "/docs/a.pdf");
+        assert_eq!(value["code"], "INVALID_FILE");
+    }
+
+    #[test]
+    fn message_only_variants_have_no_path() {
+        let null = serde_json::Value::Null;
+        assert_eq!(serialized_path(AppError::InvalidArgument("limite".to_string())), null);
+        assert_eq!(serialized_path(AppError::IoError("disco cheio".to_string())), null);
+    }
+}
@@ -1,5 +1,11 @@
 pub mod error;
 pub mod process;
+pub mod pdf;
+pub mod cache;
+pub mod backend;
 
-pub use error::AppError;
+pub use error::{AppError, PathContext};
 pub use process::*;
+pub use pdf::*;
+pub use cache::*;
+pub use backend::*;
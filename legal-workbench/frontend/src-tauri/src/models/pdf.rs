@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub creation_date: Option<String>,
+    pub producer: Option<String>,
+}
+
+/// Result of `total_page_count`. Files that couldn't be parsed contribute no
+/// pages and are listed in `corrupt`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageCountSummary {
+    pub total_pages: usize,
+    pub file_count: usize,
+    pub corrupt: Vec<String>,
+}
+
+/// Structural check of a PDF from `validate_pdf`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfValidation {
+    /// Header present and the document parsed with at least one page.
+    pub is_valid: bool,
+    pub encrypted: bool,
+    pub page_count: Option<usize>,
+    /// Problems found, fatal or not; empty for a clean file.
+    pub warnings: Vec<String>,
+}
+
+/// A word on a page and its bounding box in PDF points, with the origin at
+/// the page's top-left corner and y growing downwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordPosition {
+    pub text: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Payload of the `text-chunk` event from `extract_pdf_text_chunked`. Pages
+/// are 1-based and inclusive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextChunk {
+    pub path: String,
+    pub start_page: usize,
+    pub end_page: usize,
+    pub text: String,
+}
+
+/// Result of `detect_pdf_language`. `language` is `None` when the PDF has no
+/// text layer or the sample is too short to tell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageDetection {
+    /// ISO 639-3 code such as `por`, `eng` or `spa`.
+    pub language: Option<String>,
+    /// 0.0 to 1.0.
+    pub confidence: f64,
+    pub has_text: bool,
+}
@@ -1,22 +1,195 @@
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessFolder {
     pub path: String,
     pub name: String,
+    /// Root directory the folder was listed from (its parent).
+    pub root: String,
     pub pdf_count: usize,
     pub total_size_bytes: u64,
+    /// `total_size_bytes` formatted for display; sort on the raw field.
+    pub size_display: String,
     pub last_modified: String,
+    /// Newest mtime of the folder or anything inside it, however deep.
+    pub latest_modified: String,
+    /// `None` where the platform doesn't record creation time.
+    pub created: Option<String>,
+}
+
+/// Optional settings for `list_process_folders`; omitted fields keep the defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FolderScanOptions {
+    /// `None` keeps the historical newest-first order.
+    pub sort_by: Option<FolderSortMode>,
+    pub descending: Option<bool>,
+    pub min_pdf_count: usize,
+    pub skip_hidden: bool,
+    /// Extensions counted in `pdf_count`, matched like `PdfScanOptions::extensions`;
+    /// empty means `pdf` only.
+    pub extensions: Vec<String>,
+}
+
+impl Default for FolderScanOptions {
+    fn default() -> Self {
+        Self {
+            sort_by: None,
+            descending: None,
+            min_pdf_count: 0,
+            skip_hidden: true,
+            extensions: Vec::new(),
+        }
+    }
+}
+
+/// Optional settings for the PDF listing commands; omitted fields keep the defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PdfScanOptions {
+    /// Extensions to list, case-insensitive; empty means `pdf` only.
+    pub extensions: Vec<String>,
+    pub follow_symlinks: bool,
+    /// Deepest directory level to descend into; the scanned folder is depth 0.
+    pub max_depth: Option<usize>,
+    pub with_page_count: bool,
+    pub skip_hidden: bool,
+    /// Report `PdfFile.path` relative to this prefix; files outside it keep
+    /// their absolute path.
+    pub relative_to: Option<String>,
+    /// Order names like `doc2` before `doc10`; off keeps plain lexicographic order.
+    pub natural_sort: bool,
+    /// Check each file's `%PDF-` header: `.pdf` files without it are left
+    /// out and PDFs under any other extension are listed. Off by default
+    /// since it opens every file.
+    pub verify_magic: bool,
+}
+
+impl Default for PdfScanOptions {
+    fn default() -> Self {
+        Self {
+            extensions: Vec::new(),
+            follow_symlinks: false,
+            max_depth: None,
+            with_page_count: false,
+            skip_hidden: true,
+            relative_to: None,
+            natural_sort: false,
+            verify_magic: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderSummary {
+    pub total_folders: usize,
+    pub total_pdfs: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessFolderListing {
+    pub folders: Vec<ProcessFolder>,
+    pub summary: FolderSummary,
+}
+
+/// Result of `list_process_folders_multi`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiRootListing {
+    pub folders: Vec<ProcessFolder>,
+    /// (root, message) for roots that weren't directories or couldn't be read.
+    pub skipped: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PdfFile {
+    /// Absolute unless the listing asked for `relative_to`.
     pub path: String,
+    /// Always absolute; use this to open the file.
+    pub full_path: String,
+    /// Native bytes of `full_path` when it isn't valid UTF-8 and the strings
+    /// above are lossy. Pass it back to reopen the file.
+    pub raw_path: Option<Vec<u8>>,
+    /// Whether the file starts with `%PDF-`; only set by listings that ran
+    /// with `verify_magic`.
+    pub is_real_pdf: Option<bool>,
     pub name: String,
+    pub extension: String,
     pub size_bytes: u64,
+    /// `size_bytes` formatted for display; sort on the raw field.
+    pub size_display: String,
     pub last_modified: String,
+    pub created: Option<String>,
+    pub page_count: Option<usize>,
     pub extracted_text: Option<String>,
     pub extraction_status: ExtractionStatus,
+    /// Set when the command did its job but a follow-up step failed, e.g.
+    /// `move_pdf` moved the file but couldn't repoint its cache rows.
+    pub warning: Option<String>,
+}
+
+/// Entry of the recent documents list, from `get_recent_files`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    pub name: String,
+    /// Unix seconds.
+    pub last_opened: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfListing {
+    pub files: Vec<PdfFile>,
+    /// (path, message) for entries that could not be read.
+    pub skipped: Vec<(String, String)>,
+}
+
+impl PdfListing {
+    pub fn skip(&mut self, path: &Path, err: impl ToString) {
+        self.skipped.push((path.to_string_lossy().to_string(), err.to_string()));
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfPage {
+    pub files: Vec<PdfFile>,
+    pub total: usize,
+    pub has_more: bool,
+    pub skipped: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanProgress {
+    pub processed: usize,
+    pub current_folder: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanComplete {
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum FolderSortMode {
+    Name,
+    #[default]
+    LastModified,
+    PdfCount,
+    TotalSize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum FolderChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderChange {
+    pub kind: FolderChangeKind,
+    pub path: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -24,5 +197,75 @@ pub enum ExtractionStatus {
     Pending,
     InProgress,
     Completed,
+    /// Image-only PDF with no text layer; OCR is required.
+    NeedsOcr,
     Failed(String),
 }
+
+/// Payload of the `document-extracted` event, sent as each file finishes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionProgress {
+    pub path: String,
+    pub status: ExtractionStatus,
+    /// Start of the extracted text, when there is any.
+    pub preview: Option<String>,
+}
+
+/// Payload of the `extraction-started` event, sent before any file is processed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionStarted {
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub needs_ocr: usize,
+}
+
+/// Result of `estimate_extraction_time`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionEstimate {
+    pub total_pages: usize,
+    pub file_count: usize,
+    /// Rolling average from earlier batches, or the built-in default.
+    pub pages_per_second: f64,
+    pub estimated_seconds: f64,
+    /// 0.0 with no history, reaching 1.0 after several batches.
+    pub confidence: f64,
+    /// Batches the average is based on.
+    pub samples: u32,
+}
+
+/// Outcome of `retry_failed_extractions`: files that now succeeded, and those
+/// still failing with their last error.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryReport {
+    pub recovered: Vec<String>,
+    pub still_failed: Vec<(String, String)>,
+}
+
+/// Per-status file counts for a folder, from `extraction_status_summary`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtractionStatusCounts {
+    pub pending: usize,
+    pub in_progress: usize,
+    pub completed: usize,
+    pub needs_ocr: usize,
+    pub failed: usize,
+    pub failed_paths: Vec<String>,
+}
+
+/// Payload of the `folder-rescanned` event: how a watched folder's PDFs differ
+/// from the previous scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderRescan {
+    pub folder: String,
+    pub added: Vec<PdfFile>,
+    /// Paths of PDFs no longer present.
+    pub removed: Vec<String>,
+    /// PDFs whose size or modification time changed.
+    pub modified: Vec<PdfFile>,
+}
@@ -0,0 +1,29 @@
+use tauri_plugin_store::StoreExt;
+
+/// Store file shared with the frontend settings screen.
+const SETTINGS_STORE: &str = "settings.json";
+
+/// Absolute path of the cache database. Changing it starts a fresh cache.
+pub const CACHE_DB_PATH: &str = "cache_db_path";
+
+/// Show a desktop notification when a batch extraction finishes. Defaults to on.
+pub const NOTIFY_ON_BATCH_COMPLETE: &str = "notify_on_batch_complete";
+
+/// Record every cache write in `cache_audit_log`. Defaults to on.
+pub const CACHE_AUDIT_LOG: &str = "cache_audit_log";
+
+pub fn get_bool(app: &tauri::AppHandle, key: &str, default: bool) -> bool {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(key))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(default)
+}
+
+pub fn get_string(app: &tauri::AppHandle, key: &str) -> Option<String> {
+    let store = app.store(SETTINGS_STORE).ok()?;
+    store
+        .get(key)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .filter(|value| !value.is_empty())
+}
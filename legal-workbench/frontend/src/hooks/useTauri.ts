@@ -5,7 +5,7 @@
 import { invoke } from '@tauri-apps/api/core';
 import { open } from '@tauri-apps/plugin-dialog';
 import { isTauri } from '@/lib/tauri';
-import type { ProcessFolder, PdfFile } from '@/types/tauri';
+import type { ProcessFolder, ProcessFolderListing, PdfFile, PdfListing } from '@/types/tauri';
 
 export interface UseTauriReturn {
   // Detection
@@ -18,12 +18,17 @@ export interface UseTauriReturn {
 
   // Cache commands
   initCache: () => Promise<void>;
-  getCachedResult: (fileHash: string) => Promise<string | null>;
+  getCachedResult: (
+    fileHash: string,
+    cacheNamespace?: string,
+    ttlSeconds?: number
+  ) => Promise<string | null>;
   saveCachedResult: (
     fileHash: string,
     filePath: string,
     apiResponse: string,
-    backendUrl: string
+    backendUrl: string,
+    cacheNamespace?: string
   ) => Promise<void>;
   hashFile: (filePath: string) => Promise<string>;
 }
@@ -58,7 +63,8 @@ export function useTauri(): UseTauriReturn {
     if (!isAvailable) return [];
 
     try {
-      return await invoke<ProcessFolder[]>('list_process_folders', { rootPath });
+      const listing = await invoke<ProcessFolderListing>('list_process_folders', { rootPath });
+      return listing.folders;
     } catch (error) {
       console.error('list_process_folders error:', error);
       throw error;
@@ -72,7 +78,8 @@ export function useTauri(): UseTauriReturn {
     if (!isAvailable) return [];
 
     try {
-      return await invoke<PdfFile[]>('list_pdfs_in_folder', { folderPath });
+      const listing = await invoke<PdfListing>('list_pdfs_in_folder', { folderPath });
+      return listing.files;
     } catch (error) {
       console.error('list_pdfs_in_folder error:', error);
       throw error;
@@ -94,13 +101,22 @@ export function useTauri(): UseTauriReturn {
   };
 
   /**
-   * Get cached API result by file hash
+   * Get cached API result by file hash, optionally from a named cache and
+   * ignoring entries older than ttlSeconds
    */
-  const getCachedResult = async (fileHash: string): Promise<string | null> => {
+  const getCachedResult = async (
+    fileHash: string,
+    cacheNamespace?: string,
+    ttlSeconds?: number
+  ): Promise<string | null> => {
     if (!isAvailable) return null;
 
     try {
-      return await invoke<string | null>('get_cached_result', { fileHash });
+      return await invoke<string | null>('get_cached_result', {
+        fileHash,
+        cacheNamespace,
+        ttlSeconds,
+      });
     } catch (error) {
       console.error('get_cached_result error:', error);
       throw error;
@@ -108,13 +124,14 @@ export function useTauri(): UseTauriReturn {
   };
 
   /**
-   * Save API result to cache
+   * Save API result to cache, optionally in a named cache
    */
   const saveCachedResult = async (
     fileHash: string,
     filePath: string,
     apiResponse: string,
-    backendUrl: string
+    backendUrl: string,
+    cacheNamespace?: string
   ): Promise<void> => {
     if (!isAvailable) return;
 
@@ -124,6 +141,7 @@ export function useTauri(): UseTauriReturn {
         filePath,
         apiResponse,
         backendUrl,
+        cacheNamespace,
       });
     } catch (error) {
       console.error('save_cached_result error:', error);
@@ -6,23 +6,58 @@
 export interface ProcessFolder {
   path: string;
   name: string;
+  root: string; // directory the folder was listed from
   pdf_count: number;
   total_size_bytes: number;
+  size_display: string; // e.g. "1.4 MB"
   last_modified: string; // ISO 8601
+  latest_modified: string; // ISO 8601, newest mtime anywhere inside the folder
+  created?: string | null; // ISO 8601, absent where the platform lacks it
+}
+
+// Totals across the listed process folders
+export interface FolderSummary {
+  total_folders: number;
+  total_pdfs: number;
+  total_bytes: number;
+}
+
+export interface ProcessFolderListing {
+  folders: ProcessFolder[];
+  summary: FolderSummary;
 }
 
 // PDF file metadata
 export interface PdfFile {
-  path: string;
+  path: string; // relative when the listing was given `relative_to`
+  full_path: string; // always absolute; use this to open the file
+  raw_path?: number[] | null; // native path bytes when the name isn't valid UTF-8
+  is_real_pdf?: boolean | null; // set only when listed with verify_magic
   name: string;
+  extension: string;
   size_bytes: number;
+  size_display: string; // e.g. "1.4 MB"
   last_modified: string; // ISO 8601
+  created?: string | null; // ISO 8601, absent where the platform lacks it
+  page_count: number | null; // null when not requested (with_page_count) or unparsable
   extracted_text: string | null;
   extraction_status: ExtractionStatus;
+  warning?: string | null; // set when a follow-up step failed, e.g. cache repoint after move_pdf
+}
+
+// Result of a recursive PDF scan; unreadable entries are reported as [path, message]
+export interface PdfListing {
+  files: PdfFile[];
+  skipped: [string, string][];
 }
 
 // Extraction status enum (matches Rust enum serialization)
-export type ExtractionStatus = 'Pending' | 'InProgress' | 'Completed' | { Failed: string };
+export type ExtractionStatus =
+  | 'Pending'
+  | 'InProgress'
+  | 'Completed'
+  | 'NeedsOcr'
+  | { Failed: string };
 
 // Cache entry structure
 export interface CachedResult {
@@ -43,22 +78,30 @@ export interface HistoryEntry {
 
 // Command result types
 export interface TauriCommands {
-  list_process_folders: (args: { rootPath: string }) => Promise<ProcessFolder[]>;
-  list_pdfs_in_folder: (args: { folderPath: string }) => Promise<PdfFile[]>;
+  list_process_folders: (args: { rootPath: string }) => Promise<ProcessFolderListing>;
+  list_pdfs_in_folder: (args: { folderPath: string }) => Promise<PdfListing>;
   init_cache: () => Promise<void>;
-  get_cached_result: (args: { fileHash: string }) => Promise<string | null>;
+  // Cache commands take an optional namespace; omitted means "default"
+  get_cached_result: (args: {
+    fileHash: string;
+    ttlSeconds?: number; // entries older than this read as misses
+    cacheNamespace?: string;
+  }) => Promise<string | null>;
   save_cached_result: (args: {
     fileHash: string;
     filePath: string;
     apiResponse: string;
     backendUrl: string;
+    cacheNamespace?: string;
   }) => Promise<void>;
   hash_file: (args: { filePath: string }) => Promise<string>;
-  list_cache_entries: () => Promise<HistoryEntry[]>;
+  list_cache_entries: (args?: { cacheNamespace?: string }) => Promise<HistoryEntry[]>;
 }
 
 // Error type from Tauri commands
 export interface TauriError {
   message: string;
   code?: string;
+  kind?: string;
+  path?: string | null;
 }